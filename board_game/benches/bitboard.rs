@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+use board_game::{bitboard::BitBoard, board::BoardConfig};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+const RUN_TIME: Duration = Duration::from_secs(2);
+
+// plays random games and counts every position reached and checked for a win
+fn positions_per_second(config: BoardConfig) -> f64 {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut positions: u64 = 0;
+    let start = Instant::now();
+    while start.elapsed() < RUN_TIME {
+        let mut board = BitBoard::new(config);
+        let mut moves = board.legal_moves();
+        while !moves.is_empty() {
+            let index = moves.swap_remove(rng.random_range(0..moves.len()));
+            board.play(index);
+            positions += 1;
+            if board.is_win_at(index) {
+                break;
+            }
+        }
+    }
+    positions as f64 / start.elapsed().as_secs_f64()
+}
+
+fn main() {
+    for config in [
+        BoardConfig::new(3, 3, 3),
+        BoardConfig::new(4, 4, 4),
+        BoardConfig::new(7, 7, 5),
+        BoardConfig::new(15, 15, 5),
+        BoardConfig::new(19, 19, 5),
+    ] {
+        println!(
+            "{}x{} k={}: {:.0} positions/s",
+            config.rows,
+            config.cols,
+            config.k,
+            positions_per_second(config)
+        );
+    }
+}
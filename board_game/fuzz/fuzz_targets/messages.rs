@@ -0,0 +1,45 @@
+#![no_main]
+
+// a peer's bytes, cut into frames the way the transport cuts them; anything
+// may come back as an error but nothing may panic, and every message that
+// decodes has to encode back to itself
+
+use std::fmt::Debug;
+
+use board_game::{
+    message::{Envelope, ExecutorToManagerMsg, ManagerToExecutorMsg},
+    transport,
+    wire::{self, Encoding, Hello},
+};
+use futures::executor::block_on;
+use libfuzzer_sys::fuzz_target;
+use serde::{Serialize, de::DeserializeOwned};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((&first, mut stream)) = data.split_first() else {
+        return;
+    };
+    let encoding = if first & 1 == 0 {
+        Encoding::Json
+    } else {
+        Encoding::Binary
+    };
+    // every connection opens with a JSON hello
+    let _ = wire::decode::<Hello>(Encoding::Json, stream);
+    block_on(async {
+        while let Ok(Some(frame)) = transport::read_frame(&mut stream, encoding).await {
+            round_trip::<Envelope<ExecutorToManagerMsg>>(encoding, &frame);
+            round_trip::<Envelope<ManagerToExecutorMsg>>(encoding, &frame);
+        }
+    });
+});
+
+fn round_trip<T: Serialize + DeserializeOwned + PartialEq + Debug>(
+    encoding: Encoding,
+    frame: &[u8],
+) {
+    if let Ok(message) = wire::decode::<T>(encoding, frame) {
+        let bytes = wire::encode(encoding, &message).unwrap();
+        assert_eq!(wire::decode::<T>(encoding, &bytes).unwrap(), message);
+    }
+}
@@ -0,0 +1,43 @@
+#![no_main]
+
+// saved games are read back from files anyone can edit: a record has to be
+// either refused or replayed into positions that follow the rules
+
+use board_game::{
+    bitboard::{BitBoard, win_masks::STANDARD_CONFIGS},
+    consts::PLAYER_NUM,
+    record::GameRecord,
+};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(record) = serde_json::from_slice::<GameRecord>(data) {
+        check(&record);
+    }
+    // the same bytes as cell indices, on every standard board
+    let moves: Vec<usize> = data.iter().map(|&byte| byte as usize).collect();
+    for config in STANDARD_CONFIGS {
+        for first_player in 0..PLAYER_NUM {
+            check(&GameRecord::new(config, first_player, moves.clone()));
+        }
+    }
+});
+
+fn check(record: &GameRecord) {
+    let Ok(positions) = record.positions() else {
+        return;
+    };
+    assert_eq!(positions.len(), record.moves.len() + 1);
+    for (number, board) in positions.iter().enumerate() {
+        assert_eq!(board.move_count(), number);
+        let winners = (0..PLAYER_NUM)
+            .filter(|&player| board.has_won(player))
+            .count();
+        assert!(winners <= 1);
+        if winners == 1 {
+            assert_eq!(number, record.moves.len());
+        }
+    }
+    let last: &BitBoard = positions.last().unwrap();
+    assert!(last.move_count() <= record.config.cells());
+}
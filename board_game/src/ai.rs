@@ -0,0 +1,120 @@
+use std::{fmt::Display, str::FromStr, time::Instant};
+
+use rand::{SeedableRng, rngs::StdRng, seq::IndexedRandom};
+
+use crate::{analysis::Analyzer, bitboard::BitBoard, consts::PLAYER_NUM, metrics};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AiLevel {
+    // any legal move
+    Easy,
+    // wins or blocks a line when it can
+    Medium,
+    // perfect play where the board can be solved, medium otherwise
+    Hard,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AiError {
+    #[error("Unknown AI level: {0} (expected easy, medium or hard)")]
+    UnknownLevel(String),
+}
+
+impl FromStr for AiLevel {
+    type Err = AiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "easy" => Ok(AiLevel::Easy),
+            "medium" => Ok(AiLevel::Medium),
+            "hard" => Ok(AiLevel::Hard),
+            _ => Err(AiError::UnknownLevel(s.to_string())),
+        }
+    }
+}
+
+impl Display for AiLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AiLevel::Easy => write!(f, "easy"),
+            AiLevel::Medium => write!(f, "medium"),
+            AiLevel::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+pub struct Ai {
+    level: AiLevel,
+    rng: StdRng,
+}
+
+impl Ai {
+    pub fn new(level: AiLevel) -> Self {
+        Self {
+            level,
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    // makes the choice between equally good moves repeatable
+    pub fn set_seed(mut self, seed: u64) -> Self {
+        self.rng = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    pub fn level(&self) -> AiLevel {
+        self.level
+    }
+
+    pub fn name(&self) -> String {
+        format!("AI ({})", self.level)
+    }
+
+    pub fn choose_move(&mut self, board: &BitBoard, analyzer: &mut Analyzer) -> Option<usize> {
+        if Analyzer::is_decided(board) {
+            return None;
+        }
+        let started = Instant::now();
+        let candidates = self.candidates(board, analyzer);
+        metrics::AI_THINK_TIME.observe(started.elapsed());
+        candidates.choose(&mut self.rng).copied()
+    }
+
+    // the moves the level considers best, all equally good
+    fn candidates(&self, board: &BitBoard, analyzer: &mut Analyzer) -> Vec<usize> {
+        match self.level {
+            AiLevel::Easy => board.legal_moves(),
+            AiLevel::Hard if analyzer.can_solve(board.config()) => analyzer
+                .best_moves(board)
+                .iter()
+                .map(|analysis| analysis.index)
+                .collect(),
+            AiLevel::Medium | AiLevel::Hard => Self::tactical_moves(board),
+        }
+    }
+
+    // moves that win at once, else moves that stop the opponent from winning
+    // at once, else every legal move
+    fn tactical_moves(board: &BitBoard) -> Vec<usize> {
+        let moves = board.legal_moves();
+        let side_to_move = board.side_to_move();
+        for player in [side_to_move, (side_to_move + 1) % PLAYER_NUM] {
+            let mut board = board.clone();
+            board.set_side_to_move(player);
+            let winning: Vec<_> = moves
+                .iter()
+                .copied()
+                .filter(|&index| {
+                    board.play(index);
+                    let is_win = board.is_win_at(index);
+                    board.undo(index);
+                    is_win
+                })
+                .collect();
+            if !winning.is_empty() {
+                return winning;
+            }
+        }
+        moves
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    cmp::{Ordering, Reverse},
+    collections::HashMap,
+    fmt::Display,
+    sync::Arc,
+};
+
+use crate::{bitboard::BitBoard, board::BoardConfig, consts::PLAYER_NUM, tablebase::Tablebase};
+
+// larger boards take seconds to solve without a tablebase
+const EXACT_SEARCH_MAX_CELLS: usize = 12;
+
+// game-theoretic value for the side to move, with the number of plies until
+// the game is decided under perfect play
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GameValue {
+    Win(usize),
+    Draw,
+    Loss(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl GameValue {
+    pub fn outcome(self) -> Outcome {
+        match self {
+            GameValue::Win(_) => Outcome::Win,
+            GameValue::Draw => Outcome::Draw,
+            GameValue::Loss(_) => Outcome::Loss,
+        }
+    }
+
+    // the value of the position before the move that led to `self`
+    pub fn parent(self) -> Self {
+        match self {
+            GameValue::Win(plies) => GameValue::Loss(plies + 1),
+            GameValue::Draw => GameValue::Draw,
+            GameValue::Loss(plies) => GameValue::Win(plies + 1),
+        }
+    }
+
+    // faster wins and slower losses are better
+    fn score(self) -> isize {
+        match self {
+            GameValue::Win(plies) => isize::MAX - plies as isize,
+            GameValue::Draw => 0,
+            GameValue::Loss(plies) => isize::MIN + plies as isize,
+        }
+    }
+}
+
+impl Display for GameValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameValue::Win(plies) => write!(f, "win in {plies}"),
+            GameValue::Draw => write!(f, "draw"),
+            GameValue::Loss(plies) => write!(f, "loss in {plies}"),
+        }
+    }
+}
+
+impl Ord for GameValue {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score().cmp(&other.score())
+    }
+}
+
+impl PartialOrd for GameValue {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveAnalysis {
+    pub index: usize,
+    pub row: usize,
+    pub col: usize,
+    // value for the player making the move
+    pub value: GameValue,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveReview {
+    pub index: usize,
+    pub player: usize,
+    // values for the player making the move, before and after it
+    pub before: GameValue,
+    pub after: GameValue,
+    pub is_blunder: bool,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AnalysisError {
+    #[error("Illegal move at index {0}")]
+    IllegalMove(usize),
+}
+
+// exact solver with a transposition table keyed by canonical position hash
+pub struct Analyzer {
+    config: Option<BoardConfig>,
+    table: HashMap<u64, GameValue>,
+    tablebase: Option<Arc<Tablebase>>,
+}
+
+impl Analyzer {
+    pub fn new() -> Self {
+        Self {
+            config: None,
+            table: HashMap::new(),
+            tablebase: None,
+        }
+    }
+
+    // positions found in the tablebase are answered without searching
+    pub fn set_tablebase(mut self, tablebase: Arc<Tablebase>) -> Self {
+        self.tablebase = Some(tablebase);
+        self
+    }
+
+    pub fn tablebase(&self) -> Option<&Tablebase> {
+        self.tablebase.as_deref()
+    }
+
+    // whether positions on this board are answered quickly enough to search
+    // them while someone waits
+    pub fn can_solve(&self, config: BoardConfig) -> bool {
+        config.cells() <= EXACT_SEARCH_MAX_CELLS
+            || self
+                .tablebase()
+                .is_some_and(|tablebase| tablebase.config() == config)
+    }
+
+    pub fn evaluate(&mut self, board: &BitBoard) -> GameValue {
+        if self.config != Some(board.config()) {
+            self.config = Some(board.config());
+            self.table.clear();
+        }
+        let mut board = board.clone();
+        self.solve(&mut board)
+    }
+
+    // every legal move with its value, best first
+    pub fn analyze(&mut self, board: &BitBoard) -> Vec<MoveAnalysis> {
+        if Self::is_decided(board) {
+            return vec![];
+        }
+        let mut board = board.clone();
+        let mut analysis: Vec<_> = board
+            .legal_moves()
+            .into_iter()
+            .map(|index| {
+                board.play(index);
+                let value = self.evaluate(&board).parent();
+                board.undo(index);
+                let (row, col) = board.position(index);
+                MoveAnalysis {
+                    index,
+                    row,
+                    col,
+                    value,
+                }
+            })
+            .collect();
+        analysis.sort_by_key(|analysis| Reverse(analysis.value));
+        analysis
+    }
+
+    pub fn best_moves(&mut self, board: &BitBoard) -> Vec<MoveAnalysis> {
+        let analysis = self.analyze(board);
+        let Some(best) = analysis.first().map(|analysis| analysis.value) else {
+            return analysis;
+        };
+        analysis
+            .into_iter()
+            .take_while(|analysis| analysis.value == best)
+            .collect()
+    }
+
+    // number of plies to a forced win for the side to move, if there is one
+    pub fn forced_win(&mut self, board: &BitBoard) -> Option<usize> {
+        match self.evaluate(board) {
+            GameValue::Win(plies) => Some(plies),
+            _ => None,
+        }
+    }
+
+    // replays `moves` from an empty board and flags every move that made the
+    // result worse for the player who played it
+    pub fn review(
+        &mut self,
+        config: BoardConfig,
+        first_player: usize,
+        moves: &[usize],
+    ) -> Result<Vec<MoveReview>, AnalysisError> {
+        let mut board = BitBoard::new(config);
+        board.set_side_to_move(first_player);
+        let mut reviews = vec![];
+        for &index in moves {
+            if index >= config.cells() || board.is_occupied(index) || Self::is_decided(&board) {
+                return Err(AnalysisError::IllegalMove(index));
+            }
+            let player = board.side_to_move();
+            let before = self.evaluate(&board);
+            board.play(index);
+            let after = self.evaluate(&board).parent();
+            reviews.push(MoveReview {
+                index,
+                player,
+                before,
+                after,
+                is_blunder: after.outcome() < before.outcome(),
+            });
+        }
+        Ok(reviews)
+    }
+
+    pub fn is_decided(board: &BitBoard) -> bool {
+        board.winner().is_some() || board.is_full()
+    }
+
+    fn solve(&mut self, board: &mut BitBoard) -> GameValue {
+        // the player who just moved is the only one who can have a new line
+        let last_player = (board.side_to_move() + PLAYER_NUM - 1) % PLAYER_NUM;
+        if board.has_won(last_player) {
+            return GameValue::Loss(0);
+        }
+        if board.is_full() {
+            return GameValue::Draw;
+        }
+        if let Some(value) = self
+            .tablebase
+            .as_ref()
+            .and_then(|tablebase| tablebase.get(board))
+        {
+            return value;
+        }
+        let key = board.canonical_hash();
+        if let Some(&value) = self.table.get(&key) {
+            return value;
+        }
+        let mut best = GameValue::Loss(0);
+        for index in board.legal_moves() {
+            board.play(index);
+            let value = if board.is_win_at(index) {
+                GameValue::Win(1)
+            } else {
+                self.solve(board).parent()
+            };
+            board.undo(index);
+            best = best.max(value);
+            if best == GameValue::Win(1) {
+                break;
+            }
+        }
+        self.table.insert(key, best);
+        best
+    }
+}
+
+impl Default for Analyzer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
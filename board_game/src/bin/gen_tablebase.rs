@@ -0,0 +1,35 @@
+use std::{path::PathBuf, time::Instant};
+
+use anyhow::{Context, anyhow};
+use board_game::{board::BoardConfig, tablebase::Tablebase};
+
+// usage: gen_tablebase <rows> <cols> <k> [output file]
+fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 3 {
+        return Err(anyhow!(
+            "usage: gen_tablebase <rows> <cols> <k> [output file]"
+        ));
+    }
+    let parse = |index: usize, name: &str| {
+        args[index]
+            .parse::<usize>()
+            .with_context(|| format!("invalid {name}: {}", args[index]))
+    };
+    let config = BoardConfig::new(parse(0, "rows")?, parse(1, "cols")?, parse(2, "k")?);
+    let output = args
+        .get(3)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from(Tablebase::file_name(config)));
+
+    let start = Instant::now();
+    let tablebase = Tablebase::generate(config)?;
+    tablebase.save(&output)?;
+    println!(
+        "{} positions written to {} in {:.2?}",
+        tablebase.len(),
+        output.display(),
+        start.elapsed()
+    );
+    Ok(())
+}
@@ -0,0 +1,369 @@
+use std::sync::Arc;
+
+use symmetry::Symmetry;
+use win_masks::{DIRECTIONS, WinMasks};
+
+use crate::{
+    board::{Board, BoardConfig},
+    consts::PLAYER_NUM,
+    player::Player,
+    tile::Tile,
+};
+
+pub mod symmetry;
+pub mod win_masks;
+pub mod zobrist;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BitBoardError {
+    #[error("Position out of board: ({0}, {1})")]
+    OutOfBoard(usize, usize),
+    #[error("Position already taken: ({0}, {1})")]
+    Occupied(usize, usize),
+    #[error("Unknown player: {0}")]
+    UnknownPlayer(usize),
+    #[error("Board rows have different lengths")]
+    Ragged,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Marks {
+    // boards up to 64 cells keep one word per player
+    Small([u64; PLAYER_NUM]),
+    Large([Vec<u64>; PLAYER_NUM]),
+}
+
+#[derive(Clone, Debug)]
+pub struct BitBoard {
+    config: BoardConfig,
+    marks: Marks,
+    win_masks: Option<Arc<WinMasks>>,
+    side_to_move: usize,
+    move_count: usize,
+    hash: u64,
+}
+
+impl BitBoard {
+    pub fn new(config: BoardConfig) -> Self {
+        let (marks, win_masks) = if config.cells() <= 64 {
+            (Marks::Small([0; PLAYER_NUM]), Some(WinMasks::get(config)))
+        } else {
+            let words = config.cells().div_ceil(64);
+            (Marks::Large(std::array::from_fn(|_| vec![0; words])), None)
+        };
+        Self {
+            config,
+            marks,
+            win_masks,
+            side_to_move: 0,
+            move_count: 0,
+            hash: zobrist::side_key(0),
+        }
+    }
+
+    pub fn config(&self) -> BoardConfig {
+        self.config
+    }
+
+    pub fn side_to_move(&self) -> usize {
+        self.side_to_move
+    }
+
+    pub fn set_side_to_move(&mut self, player: usize) {
+        self.hash ^= zobrist::side_key(self.side_to_move);
+        self.side_to_move = player % PLAYER_NUM;
+        self.hash ^= zobrist::side_key(self.side_to_move);
+    }
+
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    pub fn move_count(&self) -> usize {
+        self.move_count
+    }
+
+    pub fn index(&self, row: usize, col: usize) -> usize {
+        row * self.config.cols + col
+    }
+
+    pub fn position(&self, index: usize) -> (usize, usize) {
+        (index / self.config.cols, index % self.config.cols)
+    }
+
+    pub fn owner(&self, index: usize) -> Option<usize> {
+        (0..PLAYER_NUM).find(|&player| self.test(player, index))
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Option<usize> {
+        self.owner(self.index(row, col))
+    }
+
+    pub fn is_occupied(&self, index: usize) -> bool {
+        match &self.marks {
+            Marks::Small(bits) => (bits.iter().fold(0, |all, b| all | b) >> index) & 1 == 1,
+            Marks::Large(_) => self.owner(index).is_some(),
+        }
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.move_count == self.config.cells()
+    }
+
+    pub fn legal_moves(&self) -> Vec<usize> {
+        match &self.marks {
+            Marks::Small(bits) => {
+                let cells = self.config.cells();
+                let full = if cells == 64 {
+                    u64::MAX
+                } else {
+                    (1 << cells) - 1
+                };
+                let mut empty = !bits.iter().fold(0, |all, b| all | b) & full;
+                let mut moves = Vec::with_capacity(empty.count_ones() as usize);
+                while empty != 0 {
+                    moves.push(empty.trailing_zeros() as usize);
+                    empty &= empty - 1;
+                }
+                moves
+            }
+            Marks::Large(_) => (0..self.config.cells())
+                .filter(|&index| !self.is_occupied(index))
+                .collect(),
+        }
+    }
+
+    // places a mark for the side to move without any checks, for search
+    pub fn play(&mut self, index: usize) {
+        debug_assert!(!self.is_occupied(index));
+        self.set(self.side_to_move, index, true);
+        self.set_side_to_move(self.side_to_move + 1);
+        self.move_count += 1;
+    }
+
+    pub fn undo(&mut self, index: usize) {
+        let player = self.owner(index).expect("undo on an empty cell");
+        self.set(player, index, false);
+        self.set_side_to_move(player);
+        self.move_count -= 1;
+    }
+
+    pub fn place(&mut self, row: usize, col: usize) -> Result<(), BitBoardError> {
+        if row >= self.config.rows || col >= self.config.cols {
+            return Err(BitBoardError::OutOfBoard(row, col));
+        }
+        let index = self.index(row, col);
+        if self.is_occupied(index) {
+            return Err(BitBoardError::Occupied(row, col));
+        }
+        self.play(index);
+        Ok(())
+    }
+
+    // whether the owner of `index` has a line through it
+    pub fn is_win_at(&self, index: usize) -> bool {
+        let Some(player) = self.owner(index) else {
+            return false;
+        };
+        match (&self.marks, &self.win_masks) {
+            (Marks::Small(bits), Some(masks)) => masks.by_cell[index]
+                .iter()
+                .any(|&mask| bits[player] & mask == mask),
+            _ => {
+                let (row, col) = self.position(index);
+                DIRECTIONS.iter().any(|&(dr, dc)| {
+                    1 + self.run_length(player, row, col, dr, dc)
+                        + self.run_length(player, row, col, -dr, -dc)
+                        >= self.config.k
+                })
+            }
+        }
+    }
+
+    pub fn has_won(&self, player: usize) -> bool {
+        match (&self.marks, &self.win_masks) {
+            (Marks::Small(bits), Some(masks)) => {
+                masks.all.iter().any(|&mask| bits[player] & mask == mask)
+            }
+            _ => (0..self.config.cells())
+                .any(|index| self.test(player, index) && self.is_win_at(index)),
+        }
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        (0..PLAYER_NUM).find(|&player| self.has_won(player))
+    }
+
+    // hash of the position after applying `symmetry`, without building it
+    pub fn symmetric_hash(&self, symmetry: Symmetry) -> u64 {
+        self.marked_cells().fold(
+            zobrist::side_key(self.side_to_move),
+            |hash, (player, index)| {
+                hash ^ zobrist::cell_key(player, symmetry.apply_index(self.config, index))
+            },
+        )
+    }
+
+    // the same key for every position equivalent under the board's symmetries
+    pub fn canonical_hash(&self) -> u64 {
+        self.canonical_symmetry().1
+    }
+
+    pub fn canonical_symmetry(&self) -> (Symmetry, u64) {
+        Symmetry::all(self.config)
+            .iter()
+            .map(|&symmetry| (symmetry, self.symmetric_hash(symmetry)))
+            .min_by_key(|&(_, hash)| hash)
+            .unwrap_or((Symmetry::Identity, self.hash))
+    }
+
+    // the canonical form and the symmetry that maps this board onto it
+    pub fn canonical(&self) -> (BitBoard, Symmetry) {
+        let symmetry = self.canonical_symmetry().0;
+        (self.transformed(symmetry), symmetry)
+    }
+
+    pub fn transformed(&self, symmetry: Symmetry) -> BitBoard {
+        let mut board = self.emptied();
+        for (player, index) in self.marked_cells() {
+            board.set(player, symmetry.apply_index(self.config, index), true);
+        }
+        board.move_count = self.move_count;
+        board.set_side_to_move(self.side_to_move);
+        board
+    }
+
+    // the same position with players renumbered so the side to move is player 0
+    pub fn relative_to_side(&self) -> BitBoard {
+        if self.side_to_move == 0 {
+            return self.clone();
+        }
+        let mut board = self.emptied();
+        for (player, index) in self.marked_cells() {
+            board.set(
+                (player + PLAYER_NUM - self.side_to_move) % PLAYER_NUM,
+                index,
+                true,
+            );
+        }
+        board.move_count = self.move_count;
+        board
+    }
+
+    // an empty board of the same size, sharing this one's win masks
+    fn emptied(&self) -> BitBoard {
+        let marks = match &self.marks {
+            Marks::Small(_) => Marks::Small([0; PLAYER_NUM]),
+            Marks::Large(words) => Marks::Large(std::array::from_fn(|_| vec![0; words[0].len()])),
+        };
+        BitBoard {
+            config: self.config,
+            marks,
+            win_masks: self.win_masks.clone(),
+            side_to_move: 0,
+            move_count: 0,
+            hash: zobrist::side_key(0),
+        }
+    }
+
+    // (player, index) of every mark on the board
+    pub fn marked_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        (0..self.config.cells()).filter_map(|index| self.owner(index).map(|player| (player, index)))
+    }
+
+    fn run_length(&self, player: usize, row: usize, col: usize, dr: isize, dc: isize) -> usize {
+        let (mut row, mut col) = (row as isize, col as isize);
+        let mut length = 0;
+        loop {
+            row += dr;
+            col += dc;
+            if row < 0
+                || col < 0
+                || row >= self.config.rows as isize
+                || col >= self.config.cols as isize
+                || !self.test(player, self.index(row as usize, col as usize))
+            {
+                return length;
+            }
+            length += 1;
+        }
+    }
+
+    fn test(&self, player: usize, index: usize) -> bool {
+        match &self.marks {
+            Marks::Small(bits) => (bits[player] >> index) & 1 == 1,
+            Marks::Large(words) => (words[player][index / 64] >> (index % 64)) & 1 == 1,
+        }
+    }
+
+    fn set(&mut self, player: usize, index: usize, value: bool) {
+        let (word, bit) = match &mut self.marks {
+            Marks::Small(bits) => (&mut bits[player], index),
+            Marks::Large(words) => (&mut words[player][index / 64], index % 64),
+        };
+        if value {
+            *word |= 1 << bit;
+        } else {
+            *word &= !(1 << bit);
+        }
+        self.hash ^= zobrist::cell_key(player, index);
+    }
+}
+
+impl PartialEq for BitBoard {
+    fn eq(&self, other: &Self) -> bool {
+        self.config == other.config
+            && self.marks == other.marks
+            && self.side_to_move == other.side_to_move
+    }
+}
+
+impl Eq for BitBoard {}
+
+impl TryFrom<&Board> for BitBoard {
+    type Error = BitBoardError;
+
+    fn try_from(board: &Board) -> Result<Self, Self::Error> {
+        let config = board.config;
+        if board.board.len() != config.rows
+            || board.board.iter().any(|row| row.len() != config.cols)
+        {
+            return Err(BitBoardError::Ragged);
+        }
+        let mut bit_board = BitBoard::new(config);
+        let mut counts = [0; PLAYER_NUM];
+        for (row, tiles) in board.board.iter().enumerate() {
+            for (col, tile) in tiles.iter().enumerate() {
+                if let Some(Tile {
+                    owner: Some(Player { id }),
+                }) = tile
+                {
+                    if *id >= PLAYER_NUM {
+                        return Err(BitBoardError::UnknownPlayer(*id));
+                    }
+                    let index = bit_board.index(row, col);
+                    bit_board.set(*id, index, true);
+                    counts[*id] += 1;
+                }
+            }
+        }
+        bit_board.move_count = counts.iter().sum();
+        // the player with the fewest marks moves next, ties go to the first player
+        bit_board.set_side_to_move((0..PLAYER_NUM).min_by_key(|&p| counts[p]).unwrap_or(0));
+        Ok(bit_board)
+    }
+}
+
+impl From<&BitBoard> for Board {
+    fn from(bit_board: &BitBoard) -> Self {
+        let mut board = Board::with_config(bit_board.config);
+        for (row, tiles) in board.board.iter_mut().enumerate() {
+            for (col, tile) in tiles.iter_mut().enumerate() {
+                *tile = bit_board.get(row, col).map(|id| Tile {
+                    owner: Some(Player { id }),
+                });
+            }
+        }
+        board
+    }
+}
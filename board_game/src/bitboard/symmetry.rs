@@ -0,0 +1,125 @@
+use crate::board::BoardConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    Identity,
+    // clockwise
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    // mirror left to right
+    FlipHorizontal,
+    // mirror top to bottom
+    FlipVertical,
+    Transpose,
+    AntiTranspose,
+}
+
+const SQUARE_SYMMETRIES: [Symmetry; 8] = [
+    Symmetry::Identity,
+    Symmetry::Rotate90,
+    Symmetry::Rotate180,
+    Symmetry::Rotate270,
+    Symmetry::FlipHorizontal,
+    Symmetry::FlipVertical,
+    Symmetry::Transpose,
+    Symmetry::AntiTranspose,
+];
+
+const RECTANGLE_SYMMETRIES: [Symmetry; 4] = [
+    Symmetry::Identity,
+    Symmetry::Rotate180,
+    Symmetry::FlipHorizontal,
+    Symmetry::FlipVertical,
+];
+
+impl Symmetry {
+    pub fn all(config: BoardConfig) -> &'static [Symmetry] {
+        if config.rows == config.cols {
+            &SQUARE_SYMMETRIES
+        } else {
+            &RECTANGLE_SYMMETRIES
+        }
+    }
+
+    pub fn inverse(self) -> Self {
+        match self {
+            Symmetry::Rotate90 => Symmetry::Rotate270,
+            Symmetry::Rotate270 => Symmetry::Rotate90,
+            symmetry => symmetry,
+        }
+    }
+
+    // the transposing symmetries are only valid on square boards
+    pub fn apply(self, config: BoardConfig, row: usize, col: usize) -> (usize, usize) {
+        let (last_row, last_col) = (config.rows - 1, config.cols - 1);
+        match self {
+            Symmetry::Identity => (row, col),
+            Symmetry::Rotate90 => (col, last_row - row),
+            Symmetry::Rotate180 => (last_row - row, last_col - col),
+            Symmetry::Rotate270 => (last_col - col, row),
+            Symmetry::FlipHorizontal => (row, last_col - col),
+            Symmetry::FlipVertical => (last_row - row, col),
+            Symmetry::Transpose => (col, row),
+            Symmetry::AntiTranspose => (last_col - col, last_row - row),
+        }
+    }
+
+    pub fn apply_index(self, config: BoardConfig, index: usize) -> usize {
+        let (row, col) = self.apply(config, index / config.cols, index % config.cols);
+        row * config.cols + col
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bitboard::BitBoard;
+
+    // a position with no symmetry of its own, so every transform differs
+    fn lopsided(config: BoardConfig, moves: &[(usize, usize)]) -> BitBoard {
+        let mut board = BitBoard::new(config);
+        for &(row, col) in moves {
+            board.place(row, col).unwrap();
+        }
+        board
+    }
+
+    fn assert_one_class(board: &BitBoard, symmetries: usize) {
+        let all = Symmetry::all(board.config());
+        assert_eq!(all.len(), symmetries);
+        let mut seen = vec![];
+        for &symmetry in all {
+            let transformed = board.transformed(symmetry);
+            assert_eq!(transformed.canonical_hash(), board.canonical_hash());
+            assert_eq!(transformed.hash(), board.symmetric_hash(symmetry));
+            assert_eq!(transformed.transformed(symmetry.inverse()), *board);
+            assert!(!seen.contains(&transformed), "{symmetry:?} repeats a board");
+            seen.push(transformed);
+        }
+    }
+
+    #[test]
+    fn the_eight_transforms_of_a_square_share_a_key() {
+        let board = lopsided(BoardConfig::new(4, 4, 3), &[(0, 1), (2, 3), (3, 3)]);
+        assert_one_class(&board, 8);
+    }
+
+    #[test]
+    fn the_four_transforms_of_a_rectangle_share_a_key() {
+        let board = lopsided(BoardConfig::new(3, 5, 3), &[(0, 1), (2, 3), (1, 4)]);
+        assert_one_class(&board, 4);
+    }
+
+    #[test]
+    fn different_positions_get_different_keys() {
+        let config = BoardConfig::new(3, 3, 3);
+        let corner = lopsided(config, &[(0, 0)]);
+        let edge = lopsided(config, &[(0, 1)]);
+        assert_ne!(corner.canonical_hash(), edge.canonical_hash());
+        assert_eq!(
+            corner.canonical_hash(),
+            lopsided(config, &[(2, 2)]).canonical_hash()
+        );
+    }
+}
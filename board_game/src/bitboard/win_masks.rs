@@ -0,0 +1,114 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use crate::board::BoardConfig;
+
+// (row step, col step) of the four line directions: horizontal, vertical, both diagonals
+pub const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+
+pub const STANDARD_CONFIGS: [BoardConfig; 4] = [
+    BoardConfig {
+        rows: 3,
+        cols: 3,
+        k: 3,
+    },
+    BoardConfig {
+        rows: 4,
+        cols: 4,
+        k: 4,
+    },
+    BoardConfig {
+        rows: 5,
+        cols: 5,
+        k: 4,
+    },
+    BoardConfig {
+        rows: 7,
+        cols: 7,
+        k: 5,
+    },
+];
+
+// masks of every board built so far; the standard boards are built up front
+static MASKS: OnceLock<Mutex<HashMap<BoardConfig, Arc<WinMasks>>>> = OnceLock::new();
+
+#[derive(Debug)]
+pub struct WinMasks {
+    pub all: Vec<u64>,
+    // masks of every line that goes through a cell, indexed by cell
+    pub by_cell: Vec<Vec<u64>>,
+}
+
+impl WinMasks {
+    pub fn build(config: BoardConfig) -> Self {
+        assert!(
+            config.cells() <= 64,
+            "win masks only fit boards up to 64 cells"
+        );
+        let mut all = vec![];
+        let mut by_cell = vec![vec![]; config.cells()];
+        for line in lines(config) {
+            let mask = line.iter().fold(0u64, |mask, &index| mask | (1 << index));
+            for &index in &line {
+                by_cell[index].push(mask);
+            }
+            all.push(mask);
+        }
+        Self { all, by_cell }
+    }
+
+    pub fn get(config: BoardConfig) -> Arc<Self> {
+        let masks = MASKS.get_or_init(|| {
+            Mutex::new(
+                STANDARD_CONFIGS
+                    .iter()
+                    .map(|&config| (config, Arc::new(Self::build(config))))
+                    .collect(),
+            )
+        });
+        // the masks stay valid even if a thread panicked holding the lock
+        let mut masks = masks.lock().unwrap_or_else(|err| err.into_inner());
+        masks
+            .entry(config)
+            .or_insert_with(|| Arc::new(Self::build(config)))
+            .clone()
+    }
+}
+
+// every run of k cells in a straight line, as cell indices
+pub fn lines(config: BoardConfig) -> Vec<Vec<usize>> {
+    let BoardConfig { rows, cols, k } = config;
+    let mut lines = vec![];
+    if k == 0 {
+        return lines;
+    }
+    for row in 0..rows as isize {
+        for col in 0..cols as isize {
+            for (dr, dc) in DIRECTIONS {
+                // a single cell is the same line in every direction
+                if k == 1 && (dr, dc) != DIRECTIONS[0] {
+                    continue;
+                }
+                let end_row = row + dr * (k as isize - 1);
+                let end_col = col + dc * (k as isize - 1);
+                if end_row < 0
+                    || end_row >= rows as isize
+                    || end_col < 0
+                    || end_col >= cols as isize
+                {
+                    continue;
+                }
+                lines.push(
+                    (0..k as isize)
+                        .map(|step| {
+                            ((row + dr * step) as usize) * cols + (col + dc * step) as usize
+                        })
+                        .collect(),
+                );
+            }
+        }
+    }
+    lines
+}
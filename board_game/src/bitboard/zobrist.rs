@@ -0,0 +1,69 @@
+use crate::consts::PLAYER_NUM;
+
+// keys are derived from the cell index instead of a random table, so hashes
+// stay the same across runs and can be stored in books and tablebases
+fn splitmix64(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+pub fn cell_key(player: usize, index: usize) -> u64 {
+    splitmix64((index * PLAYER_NUM + player) as u64)
+}
+
+pub fn side_key(player: usize) -> u64 {
+    splitmix64(u64::MAX - player as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bitboard::BitBoard, board::BoardConfig};
+
+    fn from_scratch(board: &BitBoard) -> u64 {
+        board
+            .marked_cells()
+            .fold(side_key(board.side_to_move()), |hash, (player, index)| {
+                hash ^ cell_key(player, index)
+            })
+    }
+
+    #[test]
+    fn the_incremental_hash_matches_one_from_scratch() {
+        for config in [BoardConfig::new(3, 3, 3), BoardConfig::new(9, 9, 5)] {
+            let mut board = BitBoard::new(config);
+            let mut played = vec![];
+            // a walk across the board that takes back every third move
+            for step in 0..config.cells() * 2 {
+                let index = (step * 7) % config.cells();
+                if board.is_occupied(index) {
+                    continue;
+                }
+                board.play(index);
+                played.push(index);
+                assert_eq!(board.hash(), from_scratch(&board));
+                if step % 3 == 2 {
+                    board.undo(played.pop().unwrap());
+                    assert_eq!(board.hash(), from_scratch(&board));
+                }
+            }
+            while let Some(index) = played.pop() {
+                board.undo(index);
+                assert_eq!(board.hash(), from_scratch(&board));
+            }
+            assert_eq!(board.hash(), BitBoard::new(config).hash());
+        }
+    }
+
+    #[test]
+    fn the_side_to_move_is_part_of_the_hash() {
+        let mut board = BitBoard::new(BoardConfig::new(3, 3, 3));
+        board.place(1, 1).unwrap();
+        let hash = board.hash();
+        board.set_side_to_move(0);
+        assert_ne!(board.hash(), hash);
+        assert_eq!(board.hash(), from_scratch(&board));
+    }
+}
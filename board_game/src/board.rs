@@ -1,17 +1,104 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
 use crate::{
-    consts::{HEIGHT, WIDTH},
+    bitboard::win_masks::DIRECTIONS,
+    consts::{HEIGHT, MAX_BOARD_SIZE, MAX_SIDE, MIN_BOARD_SIZE, WIDTH, WIN_LENGTH},
+    player::Player,
     tile::Tile,
 };
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct BoardConfig {
+    pub rows: usize,
+    pub cols: usize,
+    // number of marks in a line needed to win
+    pub k: usize,
+}
+
+impl BoardConfig {
+    pub fn new(rows: usize, cols: usize, k: usize) -> Self {
+        Self { rows, cols, k }
+    }
+
+    pub fn cells(&self) -> usize {
+        self.rows * self.cols
+    }
+
+    // small enough to build and with a line length that fits
+    pub fn is_playable(&self) -> bool {
+        (1..=MAX_SIDE).contains(&self.rows)
+            && (1..=MAX_SIDE).contains(&self.cols)
+            && (1..=self.rows.max(self.cols)).contains(&self.k)
+    }
+
+    // one of the boards games are offered on
+    pub fn is_supported(&self) -> bool {
+        self.is_playable()
+            && (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&self.rows)
+            && (MIN_BOARD_SIZE..=MAX_BOARD_SIZE).contains(&self.cols)
+            && self.k >= MIN_BOARD_SIZE
+    }
+}
+
+impl Display for BoardConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{} k={}", self.rows, self.cols, self.k)
+    }
+}
+
+impl Default for BoardConfig {
+    fn default() -> Self {
+        Self::new(HEIGHT, WIDTH, WIN_LENGTH)
+    }
+}
+
 pub struct Board {
     pub board: Vec<Vec<Option<Tile>>>,
+    pub config: BoardConfig,
 }
 
 impl Board {
     pub fn new() -> Self {
-        let board = vec![vec![None; WIDTH]; HEIGHT];
+        Self::with_config(BoardConfig::default())
+    }
 
-        Board { board }
+    pub fn with_config(config: BoardConfig) -> Self {
+        let board = vec![vec![None; config.cols]; config.rows];
+
+        Board { board, config }
+    }
+
+    pub fn owner_at(&self, row: usize, col: usize) -> Option<&Player> {
+        self.board.get(row)?.get(col)?.as_ref()?.owner.as_ref()
+    }
+
+    // walks the four lines through (row, col) and returns every cell of the lines
+    // that are at least k long, or None if the mark there did not win
+    pub fn winning_line(&self, row: usize, col: usize) -> Option<Vec<(usize, usize)>> {
+        let player = self.owner_at(row, col)?;
+        let mut cells = vec![];
+        for (dr, dc) in DIRECTIONS {
+            let mut line = vec![(row, col)];
+            for sign in [1, -1] {
+                let (mut r, mut c) = (row as isize, col as isize);
+                loop {
+                    r += dr * sign;
+                    c += dc * sign;
+                    if r < 0 || c < 0 || self.owner_at(r as usize, c as usize) != Some(player) {
+                        break;
+                    }
+                    line.push((r as usize, c as usize));
+                }
+            }
+            if line.len() >= self.config.k {
+                cells.extend(line);
+            }
+        }
+        cells.sort_unstable();
+        cells.dedup();
+        (!cells.is_empty()).then_some(cells)
     }
 }
 
@@ -20,3 +107,93 @@ impl Default for Board {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a board with the given cells of each player marked
+    fn marked(config: BoardConfig, marks: [&[(usize, usize)]; 2]) -> Board {
+        let mut board = Board::with_config(config);
+        for (id, cells) in marks.into_iter().enumerate() {
+            for &(row, col) in cells {
+                board.board[row][col] = Some(Tile {
+                    owner: Some(Player { id }),
+                });
+            }
+        }
+        board
+    }
+
+    #[test]
+    fn a_row_on_a_wide_board() {
+        let board = marked(
+            BoardConfig::new(3, 5, 3),
+            [&[(1, 2), (1, 3), (1, 4)], &[(0, 0), (2, 0)]],
+        );
+        let line = vec![(1, 2), (1, 3), (1, 4)];
+        assert_eq!(board.winning_line(1, 4), Some(line.clone()));
+        assert_eq!(board.winning_line(1, 2), Some(line));
+        assert_eq!(board.winning_line(0, 0), None);
+        assert_eq!(board.winning_line(2, 4), None);
+    }
+
+    #[test]
+    fn a_column_on_a_tall_board() {
+        let board = marked(
+            BoardConfig::new(5, 3, 3),
+            [&[(2, 0), (3, 0), (4, 0)], &[(0, 0), (1, 0)]],
+        );
+        assert_eq!(board.winning_line(3, 0), Some(vec![(2, 0), (3, 0), (4, 0)]));
+        // the other player's marks above don't extend the line
+        assert_eq!(board.winning_line(0, 0), None);
+    }
+
+    #[test]
+    fn k_shorter_than_the_side() {
+        let config = BoardConfig::new(5, 5, 3);
+        let board = marked(config, [&[(0, 1), (0, 2), (0, 3)], &[]]);
+        assert_eq!(board.winning_line(0, 2), Some(vec![(0, 1), (0, 2), (0, 3)]));
+        let board = marked(config, [&[(0, 1), (0, 3)], &[(0, 2)]]);
+        assert_eq!(board.winning_line(0, 1), None);
+        // a run longer than k is returned whole
+        let board = marked(config, [&[(4, 0), (4, 1), (4, 2), (4, 3)], &[]]);
+        assert_eq!(
+            board.winning_line(4, 0),
+            Some(vec![(4, 0), (4, 1), (4, 2), (4, 3)])
+        );
+    }
+
+    #[test]
+    fn the_anti_diagonal() {
+        let board = marked(
+            BoardConfig::new(3, 3, 3),
+            [&[(0, 2), (1, 1), (2, 0)], &[(0, 0), (0, 1)]],
+        );
+        let line = vec![(0, 2), (1, 1), (2, 0)];
+        assert_eq!(board.winning_line(2, 0), Some(line.clone()));
+        assert_eq!(board.winning_line(0, 2), Some(line));
+    }
+
+    #[test]
+    fn two_lines_through_the_last_mark() {
+        let board = marked(
+            BoardConfig::new(4, 4, 3),
+            [
+                &[(0, 0), (1, 1), (2, 2), (2, 0), (2, 1)],
+                &[(0, 1), (0, 2), (3, 3), (1, 2)],
+            ],
+        );
+        assert_eq!(
+            board.winning_line(2, 2),
+            Some(vec![(0, 0), (1, 1), (2, 0), (2, 1), (2, 2)])
+        );
+    }
+
+    #[test]
+    fn an_empty_cell_has_no_line() {
+        let board = Board::with_config(BoardConfig::new(3, 3, 3));
+        assert_eq!(board.winning_line(1, 1), None);
+        assert_eq!(board.winning_line(5, 5), None);
+    }
+}
@@ -1,3 +1,9 @@
 pub const HEIGHT: usize = 3;
 pub const WIDTH: usize = 3;
+pub const WIN_LENGTH: usize = 3;
 pub const PLAYER_NUM: usize = 2;
+// no board side may be longer than this
+pub const MAX_SIDE: usize = 64;
+// the boards games are offered on; shorter lines are won too easily
+pub const MIN_BOARD_SIZE: usize = 3;
+pub const MAX_BOARD_SIZE: usize = 9;
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info, warn};
+
+use crate::{board::BoardConfig, message::snapshot::Outcome, player::Player};
+
+// how many events a subscriber may fall behind before it misses some
+pub const EVENT_CAPACITY: usize = 256;
+
+// what happened in a game, in the order it happened; the manager changes its
+// game only by applying these, so replaying them rebuilds it exactly
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameEvent {
+    // a fresh board; everything before it belongs to an older game
+    GameStarted {
+        game_id: u64,
+        config: BoardConfig,
+        first_player: Player,
+    },
+    // the executor has taken a seat for this player
+    PlayerJoined(Player),
+    // the executor holding this player's seat has gone
+    PlayerLeft(Player),
+    MovePlayed {
+        player: Player,
+        row: usize,
+        col: usize,
+    },
+    // a move that broke the rules; the game is as it was
+    MoveRejected {
+        player: Player,
+        row: usize,
+        col: usize,
+        reason: String,
+    },
+    // the move played at this cell was taken back
+    MoveUndone {
+        row: usize,
+        col: usize,
+    },
+    // whose move it is now, after a move or an undo
+    TurnChanged(Player),
+    // never InProgress
+    GameEnded(Outcome),
+}
+
+impl GameEvent {
+    // one structured line, inside whatever game or move span is current
+    pub fn log(&self) {
+        match self {
+            GameEvent::GameStarted { first_player, .. } => {
+                info!(first_player = first_player.id, "game started")
+            }
+            GameEvent::PlayerJoined(player) => info!(player = player.id, "player joined"),
+            GameEvent::PlayerLeft(player) => info!(player = player.id, "player left"),
+            GameEvent::MovePlayed { player, row, col } => {
+                info!(player = player.id, row, col, "move played")
+            }
+            GameEvent::MoveRejected {
+                player,
+                row,
+                col,
+                reason,
+            } => warn!(player = player.id, row, col, %reason, "move rejected"),
+            GameEvent::MoveUndone { row, col } => info!(row, col, "move undone"),
+            GameEvent::TurnChanged(player) => debug!(player = player.id, "turn changed"),
+            GameEvent::GameEnded(outcome) => {
+                let winner = match outcome {
+                    Outcome::Won(player, _) => Some(player.id),
+                    _ => None,
+                };
+                info!(outcome = outcome.label(), winner, "game ended")
+            }
+        }
+    }
+}
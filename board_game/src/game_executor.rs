@@ -1,30 +1,38 @@
 use std::sync::Arc;
 
 use async_trait::async_trait;
-use tokio::sync::{
-    Mutex,
-    mpsc::{self, error::SendError},
+use tokio::{
+    sync::{Mutex, mpsc},
+    task::JoinSet,
 };
+use tokio_util::sync::CancellationToken;
+use tracing::{error, warn};
 
 use crate::{
     message::{
-        ExecutorToManagerMsg, ManagerToExecutorMsg,
-        request_message::ManagerToExecutorReqMsg,
+        Envelope, ExecutorToManagerMsg, ManagerToExecutorMsg,
+        notification::ErrorNotification,
+        request_message::{ExecutorToManagerReqMsg, ManagerToExecutorReqMsg},
         response_message::{ExecutorToManagerResMsg, ManagerToExecutorResMsg},
+        snapshot::StateSnapshot,
     },
+    outbox::{Outbox, OutboxError},
     player::Player,
+    shutdown,
 };
 
+pub type ExecutorOutbox = Outbox<ExecutorToManagerMsg, ManagerToExecutorMsg>;
+
 #[derive(Debug, thiserror::Error)]
 pub enum GameExecutorError {
-    #[error("Get rx error")]
+    #[error("No channel from the game manager")]
     GetRxError,
-    #[error("Get tx error")]
+    #[error("No channel to the game manager")]
     GetTxError,
     #[error("Message error")]
     MessageErr,
-    #[error("Send error: {0:?}")]
-    SendError(#[from] SendError<ExecutorToManagerMsg>),
+    #[error("Outbox error: {0}")]
+    OutboxError(#[from] OutboxError),
     #[error("Join error: {0:?}")]
     JoinError(#[from] tokio::task::JoinError),
     #[error("Not at game")]
@@ -33,6 +41,18 @@ pub enum GameExecutorError {
     IoError(#[from] std::io::Error),
     #[error("Unknown error: {0:?}")]
     Unknown(#[from] anyhow::Error),
+    #[error("The game manager refused: {0}")]
+    Refused(String),
+    #[error("The game manager failed: {0}")]
+    ManagerFailed(String),
+}
+
+impl GameExecutorError {
+    // whether the session has to end; a refused request only needs telling
+    // the player
+    pub fn is_fatal(&self) -> bool {
+        !matches!(self, GameExecutorError::Refused(_))
+    }
 }
 
 #[async_trait]
@@ -41,122 +61,240 @@ pub trait GameExecutor {
     where
         Self: 'static,
     {
-        let rx = self.get_rx()?;
-        let mut tasks = vec![];
-        while let Some(message) = rx.lock().await.recv().await {
-            match message {
+        let (tx, rx, token) = (self.get_tx()?, self.get_rx()?, self.get_cancel_token());
+        let mut rx = rx.lock().await;
+        let mut tasks = JoinSet::new();
+        // the first handler to fail ends the session and is returned
+        let mut failure = None;
+        let deadline = shutdown::deadline_passed(&token);
+        tokio::pin!(deadline);
+        loop {
+            let envelope = tokio::select! {
+                envelope = rx.recv() => match envelope {
+                    Some(envelope) => envelope,
+                    None => break,
+                },
+                Some(result) = tasks.join_next() => {
+                    if let Some(err) = shutdown::report::<GameExecutorError>(result) {
+                        if failure.is_none() {
+                            // a remote manager does not share our token; the
+                            // manager may be gone already
+                            let _ = tx
+                                .send(ExecutorToManagerMsg::Request(
+                                    ExecutorToManagerReqMsg::ReadyToQuitGameRequest,
+                                ))
+                                .await;
+                        }
+                        token.cancel();
+                        failure.get_or_insert(err);
+                    }
+                    continue;
+                }
+                _ = &mut deadline => {
+                    warn!("the manager did not finish quitting in time");
+                    break;
+                }
+            };
+            // replies to our own requests go straight to whoever is waiting
+            let Some(envelope) = tx.resolve(envelope) else {
+                continue;
+            };
+            let (correlation_id, game_id) = (envelope.correlation_id, envelope.game_id);
+            match envelope.body {
                 ManagerToExecutorMsg::Request(request_message) => match request_message {
                     ManagerToExecutorReqMsg::InitGameRequest => {
                         let executor = self.clone();
-                        let task = tokio::spawn(async move {
-                            executor.init_game().await?;
+                        tasks.spawn(async move {
+                            executor.init_game(correlation_id).await?;
                             Ok::<(), GameExecutorError>(())
                         });
-                        tasks.push(task);
                     }
                     ManagerToExecutorReqMsg::QuitGameRequest => {
-                        self.quit_game().await?;
-                        for task in tasks {
-                            task.abort();
+                        if let Err(err) = self.quit_game(correlation_id).await {
+                            error!("quitting failed: {err}");
+                            token.cancel();
+                            failure.get_or_insert(err);
                         }
                         break;
                     }
                     ManagerToExecutorReqMsg::ExecuteGameRequest => {
                         let executor = self.clone();
-                        let task = tokio::spawn(async move {
-                            executor.execute_game().await?;
+                        tasks.spawn(async move {
+                            executor.execute_game(correlation_id).await?;
+                            Ok::<(), GameExecutorError>(())
+                        });
+                    }
+                    ManagerToExecutorReqMsg::PlayerWinRequest(player, winning_line) => {
+                        let executor = self.clone();
+                        tasks.spawn(async move {
+                            executor
+                                .player_win(correlation_id, &player, &winning_line)
+                                .await?;
                             Ok::<(), GameExecutorError>(())
                         });
-                        tasks.push(task);
                     }
-                    ManagerToExecutorReqMsg::PlayerWinRequest(player) => {
+                    ManagerToExecutorReqMsg::SyncStateRequest(snapshot) => {
                         let executor = self.clone();
-                        let task = tokio::spawn(async move {
-                            executor.player_win(&player).await?;
+                        tasks.spawn(async move {
+                            executor.process_state_snapshot(game_id, snapshot).await?;
                             Ok::<(), GameExecutorError>(())
                         });
-                        tasks.push(task);
                     }
                 },
                 ManagerToExecutorMsg::Response(response_message) => match response_message {
                     ManagerToExecutorResMsg::ReadyToQuitGameResponse => {}
+                    ManagerToExecutorResMsg::ResetGameResponse => {}
+                    ManagerToExecutorResMsg::UndoMoveResponse => {}
+                    ManagerToExecutorResMsg::StateSnapshotResponse(snapshot) => {
+                        let executor = self.clone();
+                        tasks.spawn(async move {
+                            executor.process_state_snapshot(game_id, snapshot).await?;
+                            Ok::<(), GameExecutorError>(())
+                        });
+                    }
                     ManagerToExecutorResMsg::TileOnByPlayerResponse(is_win) => {
                         let executor = self.clone();
-                        let task = tokio::spawn(async move {
+                        tasks.spawn(async move {
                             executor.process_tile_on_by_player_response(is_win).await?;
                             Ok::<(), GameExecutorError>(())
                         });
-                        tasks.push(task);
                     }
                 },
+                // kept here rather than in a task, so it is in place before the
+                // quit request that follows it
+                ManagerToExecutorMsg::ErrorNotification(notification) if notification.fatal => {
+                    token.cancel();
+                    failure.get_or_insert(GameExecutorError::ManagerFailed(notification.message));
+                }
+                ManagerToExecutorMsg::ErrorNotification(notification) => {
+                    let executor = self.clone();
+                    tasks.spawn(async move {
+                        executor.process_error_notification(notification).await?;
+                        Ok::<(), GameExecutorError>(())
+                    });
+                }
             }
         }
-
-        Ok(())
+        tasks.shutdown().await;
+        failure.map_or(Ok(()), Err)
     }
 
-    fn get_tx(&self) -> Result<Arc<Mutex<mpsc::Sender<ExecutorToManagerMsg>>>, GameExecutorError>;
+    fn get_tx(&self) -> Result<Arc<ExecutorOutbox>, GameExecutorError>;
 
-    fn get_rx(&self)
-    -> Result<Arc<Mutex<mpsc::Receiver<ManagerToExecutorMsg>>>, GameExecutorError>;
+    // cancelled when the session should end; executors quit through the
+    // normal handshake when it is
+    fn get_cancel_token(&self) -> CancellationToken;
 
-    async fn init_game(&self) -> Result<(), GameExecutorError> {
+    fn get_rx(
+        &self,
+    ) -> Result<Arc<Mutex<mpsc::Receiver<Envelope<ManagerToExecutorMsg>>>>, GameExecutorError>;
+
+    async fn init_game(&self, correlation_id: u64) -> Result<(), GameExecutorError> {
         let tx = self.get_tx()?;
         self.init_game_impl().await?;
-        tx.lock()
-            .await
-            .send(ExecutorToManagerMsg::Response(
-                ExecutorToManagerResMsg::InitGameResponse,
-            ))
-            .await
-            .unwrap();
+        tx.reply(
+            correlation_id,
+            ExecutorToManagerMsg::Response(ExecutorToManagerResMsg::InitGameResponse),
+        )
+        .await?;
         Ok(())
     }
 
     async fn init_game_impl(&self) -> Result<(), GameExecutorError>;
 
-    async fn quit_game(&self) -> Result<(), GameExecutorError> {
+    async fn quit_game(&self, correlation_id: u64) -> Result<(), GameExecutorError> {
         let tx = self.get_tx()?;
         self.quit_game_impl().await?;
-        tx.lock()
-            .await
-            .send(ExecutorToManagerMsg::Response(
-                ExecutorToManagerResMsg::QuitGameResponse,
-            ))
-            .await?;
+        tx.reply(
+            correlation_id,
+            ExecutorToManagerMsg::Response(ExecutorToManagerResMsg::QuitGameResponse),
+        )
+        .await?;
         Ok(())
     }
 
     async fn quit_game_impl(&self) -> Result<(), GameExecutorError>;
 
-    async fn execute_game(&self) -> Result<(), GameExecutorError> {
+    async fn execute_game(&self, correlation_id: u64) -> Result<(), GameExecutorError> {
         let tx = self.get_tx()?;
         self.execute_game_impl().await?;
-        tx.lock()
-            .await
-            .send(ExecutorToManagerMsg::Response(
-                ExecutorToManagerResMsg::ExecuteGameResponse,
-            ))
-            .await?;
+        tx.reply(
+            correlation_id,
+            ExecutorToManagerMsg::Response(ExecutorToManagerResMsg::ExecuteGameResponse),
+        )
+        .await?;
         Ok(())
     }
 
     async fn execute_game_impl(&self) -> Result<(), GameExecutorError>;
 
-    async fn player_win(&self, player: &Player) -> Result<(), GameExecutorError> {
+    async fn player_win(
+        &self,
+        correlation_id: u64,
+        player: &Player,
+        winning_line: &[(usize, usize)],
+    ) -> Result<(), GameExecutorError> {
         let tx = self.get_tx()?;
-        self.player_win_impl(player).await?;
-        tx.lock()
-            .await
-            .send(ExecutorToManagerMsg::Response(
-                ExecutorToManagerResMsg::PlayerWinResponse,
+        self.player_win_impl(player, winning_line).await?;
+        tx.reply(
+            correlation_id,
+            ExecutorToManagerMsg::Response(ExecutorToManagerResMsg::PlayerWinResponse),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn player_win_impl(
+        &self,
+        player: &Player,
+        winning_line: &[(usize, usize)],
+    ) -> Result<(), GameExecutorError>;
+
+    // sends a request and waits for its reply; an error notification in
+    // place of the reply becomes a Refused error
+    async fn request(
+        &self,
+        body: ExecutorToManagerMsg,
+    ) -> Result<Envelope<ManagerToExecutorMsg>, GameExecutorError> {
+        let reply = self.get_tx()?.request(body).await?;
+        match reply.body {
+            ManagerToExecutorMsg::ErrorNotification(notification) => {
+                Err(GameExecutorError::Refused(notification.message))
+            }
+            _ => Ok(reply),
+        }
+    }
+
+    // asks the manager for the whole game, e.g. to resync after connecting
+    async fn get_state(&self) -> Result<StateSnapshot, GameExecutorError> {
+        let reply = self
+            .request(ExecutorToManagerMsg::Request(
+                ExecutorToManagerReqMsg::GetStateRequest,
             ))
             .await?;
-        Ok(())
+        match reply.body {
+            ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::StateSnapshotResponse(
+                snapshot,
+            )) => Ok(snapshot),
+            _ => Err(GameExecutorError::MessageErr),
+        }
     }
 
-    async fn player_win_impl(&self, player: &Player) -> Result<(), GameExecutorError>;
+    // the manager's view of the game with the given ID, pushed after every move
+    async fn process_state_snapshot(
+        &self,
+        game_id: u64,
+        snapshot: StateSnapshot,
+    ) -> Result<(), GameExecutorError>;
+
+    // an error the manager ran into that no request was waiting for; the
+    // game goes on
+    async fn process_error_notification(
+        &self,
+        notification: ErrorNotification,
+    ) -> Result<(), GameExecutorError>;
 
+    // a move result no request was waiting for
     async fn process_tile_on_by_player_response(
         &self,
         is_win: bool,
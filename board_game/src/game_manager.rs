@@ -1,131 +1,491 @@
-use std::sync::Arc;
+use std::sync::{Arc, Mutex as StdMutex};
 
-use tokio::sync::{
-    Mutex,
-    mpsc::{self, error::SendError},
+use tokio::{
+    sync::{Mutex, broadcast, mpsc},
+    task::JoinSet,
 };
-use tracing::{info, trace};
+use tokio_util::sync::CancellationToken;
+use tracing::{Instrument, Span, error, info_span, trace, warn};
 
 use crate::{
-    board::Board,
-    consts::{HEIGHT, WIDTH},
+    board::{Board, BoardConfig},
+    consts::PLAYER_NUM,
+    event::{EVENT_CAPACITY, GameEvent},
     message::{
-        ExecutorToManagerMsg, ManagerToExecutorMsg,
+        Envelope, ExecutorToManagerMsg, ManagerToExecutorMsg,
+        notification::ErrorNotification,
         request_message::{ExecutorToManagerReqMsg, ManagerToExecutorReqMsg},
         response_message::{ExecutorToManagerResMsg, ManagerToExecutorResMsg},
+        snapshot::{Outcome, StateSnapshot},
     },
+    metrics,
+    outbox::{Outbox, OutboxError},
     player::Player,
+    shutdown,
     tile::Tile,
 };
 
+pub type ManagerOutbox = Outbox<ManagerToExecutorMsg, ExecutorToManagerMsg>;
+
 #[derive(Debug, thiserror::Error)]
 pub enum GameManagerError {
     #[error("Channel error")]
     ChannelError,
-    #[error("Send error: {0:?}")]
-    SendError(#[from] SendError<ManagerToExecutorMsg>),
+    #[error("Outbox error: {0}")]
+    OutboxError(#[from] OutboxError),
     #[error("Message error")]
     MessageError,
     #[error("Join error: {0:?}")]
     JoinError(#[from] tokio::task::JoinError),
+    #[error("Cannot place a mark at ({0}, {1})")]
+    InvalidMove(usize, usize),
+    #[error("Cannot play on a {0} board")]
+    UnsupportedBoard(BoardConfig),
+    #[error("Cannot take back the mark at ({0}, {1}); only the last move can be")]
+    InvalidUndo(usize, usize),
+}
+
+impl GameManagerError {
+    // whether the session has to end; anything else is told to the executor
+    // and the game goes on
+    pub fn is_fatal(&self) -> bool {
+        !matches!(
+            self,
+            GameManagerError::InvalidMove(..)
+                | GameManagerError::UnsupportedBoard(_)
+                | GameManagerError::InvalidUndo(..)
+        )
+    }
+}
+
+// the board and the moves that led to it
+pub struct Game {
+    pub board: Board,
+    pub first_player: Player,
+    // who played where, oldest first
+    pub moves: Vec<(Player, usize, usize)>,
+}
+
+impl Game {
+    pub fn new(config: BoardConfig, first_player: Player) -> Self {
+        Self {
+            board: Board::with_config(config),
+            first_player,
+            moves: vec![],
+        }
+    }
+
+    // a game rebuilt from the events of a manager, starting with the one
+    // before any GameStarted
+    pub fn replay<'a>(events: impl IntoIterator<Item = &'a GameEvent>) -> Self {
+        let mut game = Self::default();
+        for event in events {
+            game.apply(event);
+        }
+        game
+    }
+
+    pub fn apply(&mut self, event: &GameEvent) {
+        match event {
+            GameEvent::GameStarted {
+                config,
+                first_player,
+                ..
+            } => *self = Self::new(*config, first_player.clone()),
+            GameEvent::MovePlayed { player, row, col } => {
+                if let Some(tile) = self
+                    .board
+                    .board
+                    .get_mut(*row)
+                    .and_then(|tiles| tiles.get_mut(*col))
+                {
+                    *tile = Some(Tile {
+                        owner: Some(player.clone()),
+                    });
+                    self.moves.push((player.clone(), *row, *col));
+                }
+            }
+            GameEvent::MoveUndone { row, col } => {
+                if let Some(tile) = self
+                    .board
+                    .board
+                    .get_mut(*row)
+                    .and_then(|tiles| tiles.get_mut(*col))
+                {
+                    *tile = None;
+                }
+                if let Some(index) = self
+                    .moves
+                    .iter()
+                    .rposition(|(_, move_row, move_col)| (move_row, move_col) == (row, col))
+                {
+                    self.moves.remove(index);
+                }
+            }
+            // these follow from the events above
+            GameEvent::PlayerJoined(_)
+            | GameEvent::PlayerLeft(_)
+            | GameEvent::MoveRejected { .. }
+            | GameEvent::TurnChanged(_)
+            | GameEvent::GameEnded(_) => {}
+        }
+    }
+
+    pub fn current_player(&self) -> Player {
+        match self.moves.last() {
+            Some((player, ..)) => Player {
+                id: (player.id + 1) % PLAYER_NUM,
+            },
+            None => self.first_player.clone(),
+        }
+    }
+
+    pub fn outcome(&self) -> Outcome {
+        // play stops at a win, so only the last move can have won
+        let winner = self.moves.last().and_then(|(player, row, col)| {
+            let line = self.board.winning_line(*row, *col)?;
+            Some((player.clone(), line))
+        });
+        match winner {
+            Some((player, line)) => Outcome::Won(player, line),
+            None if self.board.board.iter().flatten().all(Option::is_some) => Outcome::Draw,
+            None => Outcome::InProgress,
+        }
+    }
+
+    pub fn snapshot(&self) -> StateSnapshot {
+        StateSnapshot {
+            config: self.board.config,
+            board: self
+                .board
+                .board
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|tile| tile.as_ref().and_then(|tile| tile.owner.clone()))
+                        .collect()
+                })
+                .collect(),
+            current_player: self.current_player(),
+            move_number: self.moves.len(),
+            outcome: self.outcome(),
+        }
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new(BoardConfig::default(), Player::new())
+    }
 }
 
 pub struct GameManager {
-    pub board: Arc<Mutex<Board>>,
-    pub rx: Option<Arc<Mutex<mpsc::Receiver<ExecutorToManagerMsg>>>>,
-    pub tx: Option<Arc<Mutex<mpsc::Sender<ManagerToExecutorMsg>>>>,
+    pub game: Arc<Mutex<Game>>,
+    pub rx: Option<Arc<Mutex<mpsc::Receiver<Envelope<ExecutorToManagerMsg>>>>>,
+    pub tx: Option<Arc<ManagerOutbox>>,
+    // cancelled when the session should end; the manager then asks the
+    // executor to quit
+    pub cancel_token: CancellationToken,
+    events: broadcast::Sender<GameEvent>,
+    // what the logs of the game being played are filed under, replaced on
+    // every GameStarted
+    game_span: StdMutex<Span>,
 }
 
 impl GameManager {
     pub fn new() -> Self {
         Self {
-            board: Arc::new(Mutex::new(Board::new())),
+            game: Arc::new(Mutex::new(Game::default())),
             rx: None,
             tx: None,
+            cancel_token: CancellationToken::new(),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            game_span: StdMutex::new(Span::none()),
         }
     }
 
-    pub fn set_rx(mut self, rx: mpsc::Receiver<ExecutorToManagerMsg>) -> Self {
+    pub fn set_rx(mut self, rx: mpsc::Receiver<Envelope<ExecutorToManagerMsg>>) -> Self {
         self.rx = Some(Arc::new(Mutex::new(rx)));
         self
     }
 
-    pub fn set_tx(mut self, tx: mpsc::Sender<ManagerToExecutorMsg>) -> Self {
-        self.tx = Some(Arc::new(Mutex::new(tx)));
+    pub fn set_cancel_token(mut self, cancel_token: CancellationToken) -> Self {
+        self.cancel_token = cancel_token;
+        self
+    }
+
+    pub fn set_tx(mut self, tx: mpsc::Sender<Envelope<ManagerToExecutorMsg>>) -> Self {
+        self.tx = Some(Arc::new(Outbox::new(tx)));
         self
     }
 
     pub fn get_rx(
         &self,
-    ) -> Result<Arc<Mutex<mpsc::Receiver<ExecutorToManagerMsg>>>, GameManagerError> {
+    ) -> Result<Arc<Mutex<mpsc::Receiver<Envelope<ExecutorToManagerMsg>>>>, GameManagerError> {
         self.rx.clone().ok_or(GameManagerError::ChannelError)
     }
 
-    pub fn get_tx(
-        &self,
-    ) -> Result<Arc<Mutex<mpsc::Sender<ManagerToExecutorMsg>>>, GameManagerError> {
+    pub fn get_tx(&self) -> Result<Arc<ManagerOutbox>, GameManagerError> {
         self.tx.clone().ok_or(GameManagerError::ChannelError)
     }
 
+    // every event from now on; subscribe before start to see the whole session
+    pub fn subscribe(&self) -> broadcast::Receiver<GameEvent> {
+        self.events.subscribe()
+    }
+
+    fn game_span(&self) -> Span {
+        self.game_span.lock().unwrap().clone()
+    }
+
+    // the only way the game changes, so that the events tell all of it
+    fn record(&self, game: &mut Game, event: GameEvent) {
+        game.apply(&event);
+        if let GameEvent::GameStarted {
+            game_id, config, ..
+        } = &event
+        {
+            let span = info_span!(
+                parent: None,
+                "game",
+                game_id,
+                rows = config.rows,
+                cols = config.cols,
+                k = config.k
+            );
+            *self.game_span.lock().unwrap() = span;
+            self.game_span().in_scope(|| event.log());
+        } else {
+            event.log();
+        }
+        match &event {
+            GameEvent::MovePlayed { .. } => metrics::MOVES.inc(),
+            GameEvent::MoveRejected { .. } => metrics::INVALID_MOVES.inc(),
+            GameEvent::GameEnded(Outcome::Won(..)) => metrics::GAMES_WON.inc(),
+            GameEvent::GameEnded(Outcome::Draw) => metrics::GAMES_DRAWN.inc(),
+            _ => {}
+        }
+        // nobody may be listening
+        let _ = self.events.send(event);
+    }
+
+    fn record_players(&self, game: &mut Game, event: fn(Player) -> GameEvent) {
+        for id in 0..PLAYER_NUM {
+            self.record(game, event(Player { id }));
+        }
+    }
+
     pub async fn start(self: Arc<Self>) -> Result<(), GameManagerError> {
         let (tx, rx) = (self.get_tx()?, self.get_rx()?);
-        tx.lock()
-            .await
-            .send(ManagerToExecutorMsg::Request(
-                ManagerToExecutorReqMsg::InitGameRequest,
-            ))
-            .await?;
-        let mut tasks = vec![];
-        while let Some(message) = rx.lock().await.recv().await {
-            match message {
+        {
+            let mut game = self.game.lock().await;
+            let event = GameEvent::GameStarted {
+                game_id: tx.game_id(),
+                config: game.board.config,
+                first_player: game.first_player.clone(),
+            };
+            self.record(&mut game, event);
+        }
+        tx.send(ManagerToExecutorMsg::Request(
+            ManagerToExecutorReqMsg::InitGameRequest,
+        ))
+        .await?;
+        let token = self.cancel_token.clone();
+        let mut rx = rx.lock().await;
+        let mut tasks = JoinSet::new();
+        // the first handler to fail ends the session and is returned
+        let mut failure = None;
+        // whether the executor has been asked to quit
+        let mut quitting = false;
+        let deadline = shutdown::deadline_passed(&token);
+        tokio::pin!(deadline);
+        // this manager's share of the queue depth metric
+        let mut queued = 0;
+        loop {
+            let envelope = tokio::select! {
+                envelope = rx.recv() => match envelope {
+                    Some(envelope) => envelope,
+                    None => break,
+                },
+                Some(result) = tasks.join_next() => {
+                    if let Some(err) = shutdown::report::<GameManagerError>(result) {
+                        Self::fail(&tx, &token, &mut failure, err).await;
+                    }
+                    continue;
+                }
+                _ = token.cancelled(), if !quitting => {
+                    quitting = true;
+                    let manager = self.clone();
+                    tasks.spawn(
+                        async move { manager.ready_to_quit_game().await }.instrument(self.game_span()),
+                    );
+                    continue;
+                }
+                _ = &mut deadline => {
+                    warn!("the executor did not finish quitting in time");
+                    break;
+                }
+            };
+            metrics::QUEUE_DEPTH.add(rx.len() as i64 - queued);
+            queued = rx.len() as i64;
+            // replies to our own requests go straight to whoever is waiting
+            let Some(envelope) = tx.resolve(envelope) else {
+                continue;
+            };
+            let correlation_id = envelope.correlation_id;
+            let span = self.game_span();
+            // moves made before the last reset belong to a game that is gone
+            let stale = envelope.game_id != tx.game_id();
+            // what the requests handled in place came to
+            let mut handled = Ok(());
+            match envelope.body {
                 ExecutorToManagerMsg::Request(request_message) => match request_message {
+                    ExecutorToManagerReqMsg::ReadyToQuitGameRequest if quitting => {}
                     ExecutorToManagerReqMsg::ReadyToQuitGameRequest => {
+                        quitting = true;
                         let manager = self.clone();
-                        let task = tokio::spawn(async move {
-                            manager.ready_to_quit_game().await?;
-                            Ok::<(), GameManagerError>(())
-                        });
-                        tasks.push(task);
+                        tasks.spawn(
+                            async move {
+                                manager.ready_to_quit_game().await?;
+                                Ok::<(), GameManagerError>(())
+                            }
+                            .instrument(span),
+                        );
+                    }
+                    ExecutorToManagerReqMsg::TileOnByPlayerRequesst(player, ..) if stale => {
+                        warn!(player = player.id, "dropped a move from an old game");
                     }
                     ExecutorToManagerReqMsg::TileOnByPlayerRequesst(player, posx, posy) => {
+                        let span = info_span!(
+                            parent: &span,
+                            "move",
+                            player = player.id,
+                            row = posx,
+                            col = posy
+                        );
                         let manager = self.clone();
-                        let task = tokio::spawn(async move {
-                            manager.tile_on_by_player(player, posx, posy).await?;
-                            Ok::<(), GameManagerError>(())
-                        });
-                        tasks.push(task);
+                        tasks.spawn(
+                            async move {
+                                let result = manager
+                                    .tile_on_by_player(correlation_id, player, posx, posy)
+                                    .await;
+                                manager.answer_error(correlation_id, result).await
+                            }
+                            .instrument(span),
+                        );
+                    }
+                    // handled in place so no move of the old game can land after the reset
+                    ExecutorToManagerReqMsg::ResetGameRequest(config, first_player) => {
+                        handled = async {
+                            let result =
+                                self.reset_game(correlation_id, config, first_player).await;
+                            self.answer_error(correlation_id, result).await
+                        }
+                        .instrument(span)
+                        .await;
+                    }
+                    ExecutorToManagerReqMsg::UndoMoveRequest(..) if stale => {
+                        warn!("dropped an undo from an old game");
+                    }
+                    ExecutorToManagerReqMsg::UndoMoveRequest(posx, posy) => {
+                        handled = async {
+                            let result = self.undo_move(correlation_id, posx, posy).await;
+                            self.answer_error(correlation_id, result).await
+                        }
+                        .instrument(info_span!(parent: &span, "undo", row = posx, col = posy))
+                        .await;
+                    }
+                    ExecutorToManagerReqMsg::GetStateRequest => {
+                        handled = self.get_state(correlation_id).instrument(span).await;
                     }
                 },
                 ExecutorToManagerMsg::Response(response_message) => match response_message {
                     ExecutorToManagerResMsg::InitGameResponse => {
                         let manager = self.clone();
-                        let task = tokio::spawn(async move {
-                            manager.process_init_game_response().await?;
-                            Ok::<(), GameManagerError>(())
-                        });
-                        tasks.push(task);
+                        tasks.spawn(
+                            async move {
+                                manager.process_init_game_response().await?;
+                                Ok::<(), GameManagerError>(())
+                            }
+                            .instrument(span),
+                        );
                     }
                     ExecutorToManagerResMsg::QuitGameResponse => {
                         trace!("quit game");
-                        for task in tasks {
-                            task.abort();
-                        }
                         break;
                     }
                     ExecutorToManagerResMsg::ExecuteGameResponse => {
                         let manager = self.clone();
-                        let task = tokio::spawn(async move {
-                            manager.process_execute_game_response().await?;
-                            Ok::<(), GameManagerError>(())
-                        });
-                        tasks.push(task);
+                        tasks.spawn(
+                            async move {
+                                manager.process_execute_game_response().await?;
+                                Ok::<(), GameManagerError>(())
+                            }
+                            .instrument(span),
+                        );
                     }
                     ExecutorToManagerResMsg::PlayerWinResponse => {
                         trace!("player win");
                     }
                 },
             }
+            // the session winds down below as it does after a failed task
+            if let Err(err) = handled {
+                error!("request failed: {err}");
+                Self::fail(&tx, &token, &mut failure, err).await;
+                break;
+            }
+        }
+        metrics::QUEUE_DEPTH.add(-queued);
+        tasks.shutdown().await;
+        let mut game = self.game.lock().await;
+        self.game_span()
+            .in_scope(|| self.record_players(&mut game, GameEvent::PlayerLeft));
+        failure.map_or(Ok(()), Err)
+    }
+
+    // keeps the first error that ends the session and tells the executor
+    // about it, then has the session quit
+    async fn fail(
+        tx: &ManagerOutbox,
+        token: &CancellationToken,
+        failure: &mut Option<GameManagerError>,
+        err: GameManagerError,
+    ) {
+        if failure.is_none() {
+            // the executor may be gone already
+            let _ = tx
+                .send(ManagerToExecutorMsg::ErrorNotification(ErrorNotification {
+                    message: err.to_string(),
+                    fatal: true,
+                }))
+                .await;
         }
+        token.cancel();
+        failure.get_or_insert(err);
+    }
+
+    // answers the request with `correlation_id` with the error it failed
+    // with; errors that end the session are handed back instead
+    async fn answer_error(
+        &self,
+        correlation_id: u64,
+        result: Result<(), GameManagerError>,
+    ) -> Result<(), GameManagerError> {
+        let err = match result {
+            Err(err) if !err.is_fatal() => err,
+            result => return result,
+        };
+        warn!("{err}");
+        self.get_tx()?
+            .reply(
+                correlation_id,
+                ManagerToExecutorMsg::ErrorNotification(ErrorNotification {
+                    message: err.to_string(),
+                    fatal: false,
+                }),
+            )
+            .await?;
         Ok(())
     }
 
@@ -133,71 +493,125 @@ impl GameManager {
         let tx = self.get_tx()?;
         trace!("ready to quit game");
         self.ready_to_quit_game_impl().await?;
-        tx.lock()
-            .await
-            .send(ManagerToExecutorMsg::Request(
-                ManagerToExecutorReqMsg::QuitGameRequest,
-            ))
-            .await?;
+        tx.send(ManagerToExecutorMsg::Request(
+            ManagerToExecutorReqMsg::QuitGameRequest,
+        ))
+        .await?;
         Ok(())
     }
 
-    pub async fn process_init_game_response(&self) -> Result<(), GameManagerError> {
+    // the reply carries the new game's ID
+    pub async fn reset_game(
+        &self,
+        correlation_id: u64,
+        config: BoardConfig,
+        first_player: Player,
+    ) -> Result<(), GameManagerError> {
         let tx = self.get_tx()?;
-        trace!("execute the game");
-        tx.lock()
-            .await
-            .send(ManagerToExecutorMsg::Request(
-                ManagerToExecutorReqMsg::ExecuteGameRequest,
-            ))
-            .await?;
+        trace!("reset game");
+        // a remote executor may send anything
+        if !config.is_supported() {
+            return Err(GameManagerError::UnsupportedBoard(config));
+        }
+        let game_id = tx.game_id() + 1;
+        self.record(
+            &mut *self.game.lock().await,
+            GameEvent::GameStarted {
+                game_id,
+                config,
+                first_player,
+            },
+        );
+        tx.enter_game(game_id);
+        // the board a session opens with is only played after a reset
+        metrics::GAMES_STARTED.inc();
+        tx.reply(
+            correlation_id,
+            ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::ResetGameResponse),
+        )
+        .await?;
         Ok(())
     }
 
-    pub async fn process_execute_game_response(&self) -> Result<(), GameManagerError> {
+    pub async fn undo_move(
+        &self,
+        correlation_id: u64,
+        posx: usize,
+        posy: usize,
+    ) -> Result<(), GameManagerError> {
+        let tx = self.get_tx()?;
+        trace!("undo move at ({posx}, {posy})");
+        {
+            let mut game = self.game.lock().await;
+            // moves are taken back newest first, or the turns no longer alternate
+            if game
+                .moves
+                .last()
+                .is_none_or(|(_, row, col)| (*row, *col) != (posx, posy))
+            {
+                return Err(GameManagerError::InvalidUndo(posx, posy));
+            }
+            self.record(
+                &mut game,
+                GameEvent::MoveUndone {
+                    row: posx,
+                    col: posy,
+                },
+            );
+            let player = game.current_player();
+            self.record(&mut game, GameEvent::TurnChanged(player));
+        }
+        self.push_state().await?;
+        tx.reply(
+            correlation_id,
+            ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::UndoMoveResponse),
+        )
+        .await?;
         Ok(())
     }
 
-    async fn check_win(&self, player: &Player) -> bool {
-        let board = self.board.lock().await;
-        let player_tile = Tile {
-            owner: Some(player.clone()),
-        };
+    pub async fn get_state(&self, correlation_id: u64) -> Result<(), GameManagerError> {
+        let tx = self.get_tx()?;
+        let snapshot = self.game.lock().await.snapshot();
+        tx.reply(
+            correlation_id,
+            ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::StateSnapshotResponse(
+                snapshot,
+            )),
+        )
+        .await?;
+        Ok(())
+    }
 
-        // Check rows and columns
-        for i in 0..HEIGHT {
-            let mut row_win = true;
-            let mut col_win = true;
-            for j in 0..WIDTH {
-                if board.board[i][j] != Some(player_tile.clone()) {
-                    row_win = false;
-                }
-                if board.board[j][i] != Some(player_tile.clone()) {
-                    col_win = false;
-                }
-            }
-            if row_win || col_win {
-                return true;
-            }
-        }
+    // tells the executor how the board looks now, so it never drifts
+    async fn push_state(&self) -> Result<(), GameManagerError> {
+        let tx = self.get_tx()?;
+        let snapshot = self.game.lock().await.snapshot();
+        tx.send(ManagerToExecutorMsg::Request(
+            ManagerToExecutorReqMsg::SyncStateRequest(snapshot),
+        ))
+        .await?;
+        Ok(())
+    }
 
-        // Check diagonals
-        let mut diag1_win = true;
-        let mut diag2_win = true;
-        for i in 0..HEIGHT {
-            if board.board[i][i] != Some(player_tile.clone()) {
-                diag1_win = false;
-            }
-            if board.board[i][HEIGHT - 1 - i] != Some(player_tile.clone()) {
-                diag2_win = false;
-            }
-        }
+    pub async fn process_init_game_response(&self) -> Result<(), GameManagerError> {
+        let tx = self.get_tx()?;
+        self.record_players(&mut *self.game.lock().await, GameEvent::PlayerJoined);
+        trace!("execute the game");
+        tx.send(ManagerToExecutorMsg::Request(
+            ManagerToExecutorReqMsg::ExecuteGameRequest,
+        ))
+        .await?;
+        Ok(())
+    }
 
-        diag1_win || diag2_win
+    pub async fn process_execute_game_response(&self) -> Result<(), GameManagerError> {
+        Ok(())
     }
 
     async fn tile_on_by_player(
         &self,
+        correlation_id: u64,
         player: Player,
         posx: usize,
         posy: usize,
@@ -205,12 +619,12 @@ impl GameManager {
         let tx = self.get_tx()?;
         trace!("get message tile on by player");
         let is_win = self.tile_on_by_player_impl(player, posx, posy).await?;
-        tx.lock()
-            .await
-            .send(ManagerToExecutorMsg::Response(
-                ManagerToExecutorResMsg::TileOnByPlayerResponse(is_win),
-            ))
-            .await?;
+        self.push_state().await?;
+        tx.reply(
+            correlation_id,
+            ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::TileOnByPlayerResponse(is_win)),
+        )
+        .await?;
         Ok(())
     }
 
@@ -221,17 +635,49 @@ impl GameManager {
         posy: usize,
     ) -> Result<bool, GameManagerError> {
         let tx = self.get_tx()?;
-        self.board.lock().await.board[posx][posy] = Some(Tile {
-            owner: Some(player.clone()),
-        });
-        info!("tile on by player: {:?}", self.board.lock().await.board);
-        Ok(if self.check_win(&player).await {
-            tx.lock()
-                .await
-                .send(ManagerToExecutorMsg::Request(
-                    ManagerToExecutorReqMsg::PlayerWinRequest(player),
-                ))
-                .await?;
+        let winning_line = {
+            let mut game = self.game.lock().await;
+            // a remote executor may send anything
+            let free = game
+                .board
+                .board
+                .get(posx)
+                .and_then(|row| row.get(posy))
+                .is_some_and(Option::is_none);
+            if !free || game.outcome() != Outcome::InProgress || player != game.current_player() {
+                let err = GameManagerError::InvalidMove(posx, posy);
+                self.record(
+                    &mut game,
+                    GameEvent::MoveRejected {
+                        player,
+                        row: posx,
+                        col: posy,
+                        reason: err.to_string(),
+                    },
+                );
+                return Err(err);
+            }
+            self.record(
+                &mut game,
+                GameEvent::MovePlayed {
+                    player: player.clone(),
+                    row: posx,
+                    col: posy,
+                },
+            );
+            let event = match game.outcome() {
+                Outcome::InProgress => GameEvent::TurnChanged(game.current_player()),
+                outcome => GameEvent::GameEnded(outcome),
+            };
+            self.record(&mut game, event);
+            game.board.winning_line(posx, posy)
+        };
+        Ok(if let Some(winning_line) = winning_line {
+            // the executor has heard about the win before the move's reply
+            tx.request(ManagerToExecutorMsg::Request(
+                ManagerToExecutorReqMsg::PlayerWinRequest(player, winning_line),
+            ))
+            .await?;
             true
         } else {
             false
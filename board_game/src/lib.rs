@@ -2,6 +2,8 @@ pub mod printer;
 
 pub mod board;
 
+pub mod bitboard;
+
 pub mod tile;
 
 pub mod consts;
@@ -10,6 +12,28 @@ pub mod player;
 
 pub mod game_manager;
 
+pub mod event;
+
 pub mod message;
 
-pub mod game_executor;
\ No newline at end of file
+pub mod game_executor;
+
+pub mod analysis;
+
+pub mod tablebase;
+
+pub mod stats;
+
+pub mod record;
+
+pub mod ai;
+
+pub mod transport;
+
+pub mod outbox;
+
+pub mod wire;
+
+pub mod shutdown;
+
+pub mod metrics;
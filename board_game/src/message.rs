@@ -1,16 +1,51 @@
+use serde::{Deserialize, Serialize};
+
+use notification::ErrorNotification;
 use request_message::{ExecutorToManagerReqMsg, ManagerToExecutorReqMsg};
 use response_message::{ExecutorToManagerResMsg, ManagerToExecutorResMsg};
 
+pub mod notification;
 pub mod request_message;
 pub mod response_message;
+pub mod snapshot;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ManagerToExecutorMsg {
     Request(ManagerToExecutorReqMsg),
     Response(ManagerToExecutorResMsg),
+    ErrorNotification(ErrorNotification),
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExecutorToManagerMsg {
     Request(ExecutorToManagerReqMsg),
     Response(ExecutorToManagerResMsg),
 }
+
+// what every message travels in
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Envelope<T> {
+    // a request's own ID; a response repeats the ID of the request it answers
+    pub correlation_id: u64,
+    // counts the messages one side has sent
+    pub sequence: u64,
+    // the game the message belongs to; the manager starts a new one on every reset
+    pub game_id: u64,
+    pub body: T,
+}
+
+pub trait Message {
+    fn is_response(&self) -> bool;
+}
+
+impl Message for ManagerToExecutorMsg {
+    // an error notification answers the request that failed, if any
+    fn is_response(&self) -> bool {
+        !matches!(self, ManagerToExecutorMsg::Request(_))
+    }
+}
+
+impl Message for ExecutorToManagerMsg {
+    fn is_response(&self) -> bool {
+        matches!(self, ExecutorToManagerMsg::Response(_))
+    }
+}
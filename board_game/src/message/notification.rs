@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+// an error the manager ran into; it answers the request that caused it, or
+// comes on its own when the session has to end
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ErrorNotification {
+    pub message: String,
+    // the manager quits right after a fatal error
+    pub fatal: bool,
+}
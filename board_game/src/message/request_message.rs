@@ -1,14 +1,27 @@
-use crate::player::Player;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone)]
+use super::snapshot::StateSnapshot;
+use crate::{board::BoardConfig, player::Player};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ManagerToExecutorReqMsg {
     InitGameRequest,
     QuitGameRequest,
     ExecuteGameRequest,
-    PlayerWinRequest(Player),
+    // the winner and every cell of its winning line
+    PlayerWinRequest(Player, Vec<(usize, usize)>),
+    // the state after a move, sent without being asked; needs no reply
+    SyncStateRequest(StateSnapshot),
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExecutorToManagerReqMsg {
     ReadyToQuitGameRequest,
     TileOnByPlayerRequesst(Player, usize, usize),
+    // clear the board for a new game on a board of this config, opened by
+    // the given player
+    ResetGameRequest(BoardConfig, Player),
+    // clear a cell again, taking back the move played there
+    UndoMoveRequest(usize, usize),
+    // answered with a StateSnapshotResponse
+    GetStateRequest,
 }
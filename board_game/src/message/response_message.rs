@@ -1,10 +1,17 @@
-#[derive(Debug, Clone)]
+use serde::{Deserialize, Serialize};
+
+use super::snapshot::StateSnapshot;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ManagerToExecutorResMsg {
     ReadyToQuitGameResponse,
     // bool value to indicate whether a player had won
     TileOnByPlayerResponse(bool),
+    ResetGameResponse,
+    UndoMoveResponse,
+    StateSnapshotResponse(StateSnapshot),
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ExecutorToManagerResMsg {
     InitGameResponse,
     QuitGameResponse,
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{board::BoardConfig, player::Player};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Outcome {
+    InProgress,
+    // the winner and every cell of its winning line
+    Won(Player, Vec<(usize, usize)>),
+    Draw,
+}
+
+impl Outcome {
+    // the name logs use
+    pub fn label(&self) -> &'static str {
+        match self {
+            Outcome::InProgress => "in_progress",
+            Outcome::Won(..) => "won",
+            Outcome::Draw => "draw",
+        }
+    }
+}
+
+// everything a front end needs to draw the game, so it never has to
+// rebuild the board from move results
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub config: BoardConfig,
+    // the owner of every cell, row by row
+    pub board: Vec<Vec<Option<Player>>>,
+    pub current_player: Player,
+    // marks on the board, 0 before the first move
+    pub move_number: usize,
+    pub outcome: Outcome,
+}
@@ -0,0 +1,247 @@
+use std::time::Duration;
+#[cfg(feature = "metrics")]
+use std::{
+    fmt::Write,
+    sync::atomic::{AtomicI64, AtomicU64, Ordering},
+};
+
+// what a hosted server reports; without the metrics feature every update
+// compiles to nothing
+pub static GAMES_STARTED: Counter = Counter::new();
+
+pub static GAMES_WON: Counter = Counter::new();
+
+pub static GAMES_DRAWN: Counter = Counter::new();
+
+pub static MOVES: Counter = Counter::new();
+
+pub static INVALID_MOVES: Counter = Counter::new();
+
+pub static AI_THINK_TIME: Histogram = Histogram::new();
+
+// messages waiting for a manager, across all of them
+pub static QUEUE_DEPTH: Gauge = Gauge::new();
+
+pub static CONNECTIONS: Gauge = Gauge::new();
+
+// upper bounds of the AI think time buckets, in seconds
+#[cfg(feature = "metrics")]
+const BUCKETS: [f64; 5] = [0.001, 0.01, 0.1, 1.0, 10.0];
+
+pub struct Counter {
+    #[cfg(feature = "metrics")]
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn inc(&self) {
+        #[cfg(feature = "metrics")]
+        self.value.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+impl Default for Counter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Gauge {
+    #[cfg(feature = "metrics")]
+    value: AtomicI64,
+}
+
+impl Gauge {
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            value: AtomicI64::new(0),
+        }
+    }
+
+    pub fn add(&self, delta: i64) {
+        #[cfg(feature = "metrics")]
+        self.value.fetch_add(delta, Ordering::Relaxed);
+        #[cfg(not(feature = "metrics"))]
+        let _ = delta;
+    }
+}
+
+impl Default for Gauge {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Histogram {
+    // observations at or below each bucket's bound, the last one for the rest
+    #[cfg(feature = "metrics")]
+    buckets: [AtomicU64; 6],
+    #[cfg(feature = "metrics")]
+    sum_micros: AtomicU64,
+}
+
+impl Histogram {
+    pub const fn new() -> Self {
+        Self {
+            #[cfg(feature = "metrics")]
+            buckets: [const { AtomicU64::new(0) }; 6],
+            #[cfg(feature = "metrics")]
+            sum_micros: AtomicU64::new(0),
+        }
+    }
+
+    pub fn observe(&self, duration: Duration) {
+        #[cfg(feature = "metrics")]
+        {
+            let seconds = duration.as_secs_f64();
+            let bucket = BUCKETS
+                .iter()
+                .position(|&bound| seconds <= bound)
+                .unwrap_or(BUCKETS.len());
+            self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+            self.sum_micros
+                .fetch_add(duration.as_micros() as u64, Ordering::Relaxed);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = duration;
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// every metric in the Prometheus text format
+#[cfg(feature = "metrics")]
+pub fn render() -> String {
+    let mut text = String::new();
+    let counter = |text: &mut String, name: &str, help: &str, counter: &Counter| {
+        let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} counter");
+        let _ = writeln!(text, "{name} {}", counter.value.load(Ordering::Relaxed));
+    };
+    let gauge = |text: &mut String, name: &str, help: &str, gauge: &Gauge| {
+        let _ = writeln!(text, "# HELP {name} {help}\n# TYPE {name} gauge");
+        let _ = writeln!(text, "{name} {}", gauge.value.load(Ordering::Relaxed));
+    };
+    counter(
+        &mut text,
+        "tictactoe_games_started_total",
+        "Games started.",
+        &GAMES_STARTED,
+    );
+    let name = "tictactoe_games_finished_total";
+    let _ = writeln!(text, "# HELP {name} Games finished, by outcome.");
+    let _ = writeln!(text, "# TYPE {name} counter");
+    for (outcome, counter) in [("won", &GAMES_WON), ("draw", &GAMES_DRAWN)] {
+        let value = counter.value.load(Ordering::Relaxed);
+        let _ = writeln!(text, "{name}{{outcome=\"{outcome}\"}} {value}");
+    }
+    counter(
+        &mut text,
+        "tictactoe_moves_total",
+        "Moves played; rate() gives moves per second.",
+        &MOVES,
+    );
+    counter(
+        &mut text,
+        "tictactoe_invalid_moves_total",
+        "Moves rejected by the rules.",
+        &INVALID_MOVES,
+    );
+    let name = "tictactoe_ai_think_seconds";
+    let _ = writeln!(text, "# HELP {name} Time the AI took to pick a move.");
+    let _ = writeln!(text, "# TYPE {name} histogram");
+    let mut count = 0;
+    for (index, bucket) in AI_THINK_TIME.buckets.iter().enumerate() {
+        count += bucket.load(Ordering::Relaxed);
+        let bound = BUCKETS
+            .get(index)
+            .map_or("+Inf".to_string(), |bound| bound.to_string());
+        let _ = writeln!(text, "{name}_bucket{{le=\"{bound}\"}} {count}");
+    }
+    let sum = AI_THINK_TIME.sum_micros.load(Ordering::Relaxed) as f64 / 1e6;
+    let _ = writeln!(text, "{name}_sum {sum}\n{name}_count {count}");
+    gauge(
+        &mut text,
+        "tictactoe_queue_depth",
+        "Messages waiting for a game manager.",
+        &QUEUE_DEPTH,
+    );
+    gauge(
+        &mut text,
+        "tictactoe_connections",
+        "Clients connected to the server.",
+        &CONNECTIONS,
+    );
+    text
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use super::*;
+
+    // the value on the sample line for `series`
+    fn sample(text: &str, series: &str) -> f64 {
+        text.lines()
+            .find_map(|line| line.strip_prefix(series)?.strip_prefix(' '))
+            .unwrap_or_else(|| panic!("no sample for {series}"))
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn render_reports_every_metric_with_its_type() {
+        // other tests may count too, so only the changes are compared
+        let before = render();
+        MOVES.inc();
+        MOVES.inc();
+        GAMES_WON.inc();
+        QUEUE_DEPTH.add(3);
+        QUEUE_DEPTH.add(-1);
+        AI_THINK_TIME.observe(Duration::from_millis(50));
+        let after = render();
+        QUEUE_DEPTH.add(-2);
+
+        for (name, kind) in [
+            ("tictactoe_games_started_total", "counter"),
+            ("tictactoe_games_finished_total", "counter"),
+            ("tictactoe_moves_total", "counter"),
+            ("tictactoe_invalid_moves_total", "counter"),
+            ("tictactoe_ai_think_seconds", "histogram"),
+            ("tictactoe_queue_depth", "gauge"),
+            ("tictactoe_connections", "gauge"),
+        ] {
+            assert!(
+                after.contains(&format!("# TYPE {name} {kind}\n")),
+                "{name} is not a {kind}"
+            );
+        }
+        let change = |series| sample(&after, series) - sample(&before, series);
+        assert_eq!(change("tictactoe_moves_total"), 2.0);
+        assert_eq!(
+            change("tictactoe_games_finished_total{outcome=\"won\"}"),
+            1.0
+        );
+        assert_eq!(change("tictactoe_queue_depth"), 2.0);
+        assert_eq!(
+            change("tictactoe_ai_think_seconds_bucket{le=\"0.01\"}"),
+            0.0
+        );
+        assert_eq!(change("tictactoe_ai_think_seconds_bucket{le=\"0.1\"}"), 1.0);
+        assert_eq!(
+            change("tictactoe_ai_think_seconds_bucket{le=\"+Inf\"}"),
+            1.0
+        );
+        assert_eq!(change("tictactoe_ai_think_seconds_count"), 1.0);
+    }
+}
@@ -0,0 +1,125 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        Mutex as StdMutex,
+        atomic::{AtomicU64, Ordering},
+    },
+    time::Duration,
+};
+
+use tokio::sync::{Mutex, mpsc, oneshot};
+
+use crate::message::{Envelope, Message};
+
+// how long a request waits for its reply by default
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, thiserror::Error)]
+pub enum OutboxError {
+    #[error("Channel closed")]
+    Closed,
+    #[error("No reply to request {0} in time")]
+    Timeout(u64),
+    #[error("Request {0} was dropped before its reply came")]
+    Dropped(u64),
+}
+
+// the sending half of one side of the protocol: stamps every message with an
+// envelope and pairs replies with the requests waiting for them
+pub struct Outbox<Out, In> {
+    tx: Mutex<mpsc::Sender<Envelope<Out>>>,
+    next_id: AtomicU64,
+    sequence: AtomicU64,
+    game_id: AtomicU64,
+    timeout: Duration,
+    pending: StdMutex<HashMap<u64, oneshot::Sender<Envelope<In>>>>,
+}
+
+impl<Out, In: Message> Outbox<Out, In> {
+    pub fn new(tx: mpsc::Sender<Envelope<Out>>) -> Self {
+        Self {
+            tx: Mutex::new(tx),
+            next_id: AtomicU64::new(0),
+            sequence: AtomicU64::new(0),
+            game_id: AtomicU64::new(0),
+            timeout: REQUEST_TIMEOUT,
+            pending: StdMutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn game_id(&self) -> u64 {
+        self.game_id.load(Ordering::SeqCst)
+    }
+
+    // stamps every message from now on with `game_id`
+    pub fn enter_game(&self, game_id: u64) {
+        self.game_id.store(game_id, Ordering::SeqCst);
+    }
+
+    // sends a message under a fresh correlation ID and returns the ID
+    pub async fn send(&self, body: Out) -> Result<u64, OutboxError> {
+        let correlation_id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.send_with_id(correlation_id, body).await?;
+        Ok(correlation_id)
+    }
+
+    // answers the request with `correlation_id`
+    pub async fn reply(&self, correlation_id: u64, body: Out) -> Result<(), OutboxError> {
+        self.send_with_id(correlation_id, body).await
+    }
+
+    // sends a request and waits for the response that carries its ID
+    pub async fn request(&self, body: Out) -> Result<Envelope<In>, OutboxError> {
+        let correlation_id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (reply_tx, reply_rx) = oneshot::channel();
+        self.pending
+            .lock()
+            .unwrap()
+            .insert(correlation_id, reply_tx);
+        let result = match self.send_with_id(correlation_id, body).await {
+            Ok(()) => match tokio::time::timeout(self.timeout, reply_rx).await {
+                Ok(Ok(reply)) => Ok(reply),
+                Ok(Err(_)) => Err(OutboxError::Dropped(correlation_id)),
+                Err(_) => Err(OutboxError::Timeout(correlation_id)),
+            },
+            Err(err) => Err(err),
+        };
+        self.pending.lock().unwrap().remove(&correlation_id);
+        result
+    }
+
+    // hands a response to the request waiting for it; everything else, and
+    // responses nobody waits for any more, is given back
+    pub fn resolve(&self, envelope: Envelope<In>) -> Option<Envelope<In>> {
+        if !envelope.body.is_response() {
+            return Some(envelope);
+        }
+        let waiting = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&envelope.correlation_id);
+        match waiting {
+            Some(reply_tx) => reply_tx.send(envelope).err(),
+            None => Some(envelope),
+        }
+    }
+
+    // the sequence number is taken under the sender's lock so it follows the
+    // order messages go out in
+    async fn send_with_id(&self, correlation_id: u64, body: Out) -> Result<(), OutboxError> {
+        let tx = self.tx.lock().await;
+        let envelope = Envelope {
+            correlation_id,
+            sequence: self.sequence.fetch_add(1, Ordering::SeqCst),
+            game_id: self.game_id(),
+            body,
+        };
+        tx.send(envelope).await.map_err(|_| OutboxError::Closed)
+    }
+}
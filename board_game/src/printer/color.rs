@@ -13,6 +13,8 @@ pub enum ColorType {
 pub enum ColorError {
     #[error("Invalid hex string: {0:?}")]
     HexErr(#[from] std::num::ParseIntError),
+    #[error("Hex color must have 6 digits: {0}")]
+    LengthErr(String),
 }
 
 impl From<ColorType> for u8 {
@@ -29,6 +31,9 @@ impl Color {
     }
     pub fn new_from_hex_str(hex: &str) -> Result<Self, ColorError> {
         let hex = hex.trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return Err(ColorError::LengthErr(hex.to_string()));
+        }
         let red = u8::from_str_radix(&hex[0..2], 16)?;
         let green = u8::from_str_radix(&hex[2..4], 16)?;
         let blue = u8::from_str_radix(&hex[4..6], 16)?;
@@ -0,0 +1,76 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{analysis::Analyzer, bitboard::BitBoard, board::BoardConfig, consts::PLAYER_NUM};
+
+#[derive(Debug, thiserror::Error)]
+pub enum RecordError {
+    #[error("Io error: {0:?}")]
+    IoError(#[from] std::io::Error),
+    #[error("Json error: {0:?}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Board {0} cannot be played")]
+    UnplayableBoard(BoardConfig),
+    #[error("Unknown first player: {0}")]
+    UnknownPlayer(usize),
+    // move number and the cell it was played on
+    #[error("Move {0} is illegal: cell {1}")]
+    IllegalMove(usize, usize),
+}
+
+// the moves of one game, enough to replay or analyze it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameRecord {
+    pub config: BoardConfig,
+    pub first_player: usize,
+    // cell indices in the order they were played
+    pub moves: Vec<usize>,
+}
+
+impl GameRecord {
+    pub fn new(config: BoardConfig, first_player: usize, moves: Vec<usize>) -> Self {
+        Self {
+            config,
+            first_player,
+            moves,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, RecordError> {
+        Ok(serde_json::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), RecordError> {
+        if let Some(parent) = path.as_ref().parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // every position of the game, from the empty board to the last move
+    pub fn positions(&self) -> Result<Vec<BitBoard>, RecordError> {
+        // a record is a file anyone can edit
+        if !self.config.is_playable() {
+            return Err(RecordError::UnplayableBoard(self.config));
+        }
+        if self.first_player >= PLAYER_NUM {
+            return Err(RecordError::UnknownPlayer(self.first_player));
+        }
+        let mut board = BitBoard::new(self.config);
+        board.set_side_to_move(self.first_player);
+        let mut positions = vec![board.clone()];
+        for (number, &index) in self.moves.iter().enumerate() {
+            if index >= self.config.cells()
+                || board.is_occupied(index)
+                || Analyzer::is_decided(&board)
+            {
+                return Err(RecordError::IllegalMove(number + 1, index));
+            }
+            board.play(index);
+            positions.push(board.clone());
+        }
+        Ok(positions)
+    }
+}
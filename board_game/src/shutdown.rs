@@ -0,0 +1,27 @@
+use std::{fmt::Display, time::Duration};
+
+use tokio::task::JoinError;
+use tokio_util::sync::CancellationToken;
+use tracing::error;
+
+// how long the quit handshake may take once shutdown is requested
+pub const SHUTDOWN_DEADLINE: Duration = Duration::from_secs(3);
+
+// resolves once shutdown has been requested and the deadline has passed
+pub async fn deadline_passed(token: &CancellationToken) {
+    token.cancelled().await;
+    tokio::time::sleep(SHUTDOWN_DEADLINE).await;
+}
+
+// logs a finished handler task and hands back its error; a task aborted
+// during shutdown is not a failure
+pub fn report<E: Display + From<JoinError>>(result: Result<Result<(), E>, JoinError>) -> Option<E> {
+    let err = match result {
+        Ok(Ok(())) => return None,
+        Err(err) if err.is_cancelled() => return None,
+        Ok(Err(err)) => err,
+        Err(err) => E::from(err),
+    };
+    error!("task failed: {err}");
+    Some(err)
+}
@@ -0,0 +1,197 @@
+use std::{
+    collections::BTreeMap,
+    fs::{self, File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::consts::PLAYER_NUM;
+
+#[derive(Debug, thiserror::Error)]
+pub enum StatsError {
+    #[error("Io error: {0:?}")]
+    IoError(#[from] std::io::Error),
+    #[error("Json error: {0:?}")]
+    JsonError(#[from] serde_json::Error),
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GameResult {
+    // indexed by player id
+    pub players: [String; PLAYER_NUM],
+    pub variant: String,
+    // None on a draw
+    pub winner: Option<usize>,
+    pub moves: usize,
+    pub duration_secs: f64,
+    // unix timestamp in seconds
+    pub finished_at: u64,
+}
+
+// finished games, one JSON object per line
+pub struct StatsStore {
+    path: PathBuf,
+}
+
+impl StatsStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn record(&self, result: &GameResult) -> Result<(), StatsError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        writeln!(file, "{}", serde_json::to_string(result)?)?;
+        Ok(())
+    }
+
+    // a line that cannot be read, e.g. one cut short by a crash, is skipped
+    // so the rest of the history survives
+    pub fn load(&self) -> Result<Vec<GameResult>, StatsError> {
+        let file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+        let mut results = vec![];
+        for (number, line) in BufReader::new(file).split(b'\n').enumerate() {
+            let line = line?;
+            if line.trim_ascii().is_empty() {
+                continue;
+            }
+            match serde_json::from_slice(&line) {
+                Ok(result) => results.push(result),
+                Err(err) => warn!(
+                    "skipped line {} of {}: {err}",
+                    number + 1,
+                    self.path.display()
+                ),
+            }
+        }
+        Ok(results)
+    }
+
+    pub fn reset(&self) -> Result<(), StatsError> {
+        match fs::remove_file(&self.path) {
+            Err(err) if err.kind() != std::io::ErrorKind::NotFound => Err(err.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Streak {
+    Wins(usize),
+    Draws(usize),
+    Losses(usize),
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlayerStats {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    pub current_streak: Option<Streak>,
+    pub best_win_streak: usize,
+    pub total_moves: usize,
+    pub total_duration_secs: f64,
+}
+
+impl PlayerStats {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    pub fn average_moves(&self) -> f64 {
+        self.total_moves as f64 / self.games().max(1) as f64
+    }
+
+    pub fn average_duration_secs(&self) -> f64 {
+        self.total_duration_secs / self.games().max(1) as f64
+    }
+
+    fn add(&mut self, result: &GameResult, player: usize) {
+        let streak = match result.winner {
+            Some(winner) if winner == player => {
+                self.wins += 1;
+                Streak::Wins(1)
+            }
+            Some(_) => {
+                self.losses += 1;
+                Streak::Losses(1)
+            }
+            None => {
+                self.draws += 1;
+                Streak::Draws(1)
+            }
+        };
+        self.current_streak = Some(match (self.current_streak, streak) {
+            (Some(Streak::Wins(n)), Streak::Wins(_)) => Streak::Wins(n + 1),
+            (Some(Streak::Draws(n)), Streak::Draws(_)) => Streak::Draws(n + 1),
+            (Some(Streak::Losses(n)), Streak::Losses(_)) => Streak::Losses(n + 1),
+            (_, streak) => streak,
+        });
+        if let Some(Streak::Wins(n)) = self.current_streak {
+            self.best_win_streak = self.best_win_streak.max(n);
+        }
+        self.total_moves += result.moves;
+        self.total_duration_secs += result.duration_secs;
+    }
+}
+
+// results between two players, seen from the first one
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeadToHead {
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub players: BTreeMap<String, PlayerStats>,
+    // keyed by the two names in sorted order
+    pub head_to_head: BTreeMap<(String, String), HeadToHead>,
+}
+
+impl Statistics {
+    // results must be in the order the games were played
+    pub fn from_results(results: &[GameResult]) -> Self {
+        let mut statistics = Statistics::default();
+        for result in results {
+            for (player, name) in result.players.iter().enumerate() {
+                // a name on both sides played itself; the game counts once,
+                // from the first of its sides
+                if result.players[..player].contains(name) {
+                    continue;
+                }
+                statistics
+                    .players
+                    .entry(name.clone())
+                    .or_default()
+                    .add(result, player);
+            }
+            let [first, second] = &result.players;
+            let (key, first_id) = if first <= second {
+                ((first.clone(), second.clone()), 0)
+            } else {
+                ((second.clone(), first.clone()), 1)
+            };
+            let record = statistics.head_to_head.entry(key).or_default();
+            match result.winner {
+                Some(winner) if winner == first_id => record.wins += 1,
+                Some(_) => record.losses += 1,
+                None => record.draws += 1,
+            }
+        }
+        statistics
+    }
+}
@@ -0,0 +1,186 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use crate::{
+    analysis::{Analyzer, GameValue},
+    bitboard::BitBoard,
+    board::BoardConfig,
+};
+
+const MAGIC: &[u8; 4] = b"TTTB";
+const VERSION: u8 = 1;
+// plies are stored in the low 6 bits of an entry
+const MAX_CELLS: usize = 63;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TablebaseError {
+    #[error("Board too large for a tablebase: {0} cells")]
+    TooLarge(usize),
+    #[error("Not a tablebase file")]
+    BadMagic,
+    #[error("Unsupported tablebase version: {0}")]
+    BadVersion(u8),
+    #[error("Invalid entry value: {0:#04x}")]
+    BadValue(u8),
+    #[error("Tablebase is for a {found} board, not {expected}")]
+    WrongBoard {
+        expected: BoardConfig,
+        found: BoardConfig,
+    },
+    #[error("Io error: {0:?}")]
+    IoError(#[from] std::io::Error),
+}
+
+// solved value of every reachable position, keyed by the canonical hash of the
+// position seen from the side to move
+pub struct Tablebase {
+    config: BoardConfig,
+    // sorted by key for binary search
+    entries: Vec<(u64, GameValue)>,
+}
+
+impl Tablebase {
+    pub fn generate(config: BoardConfig) -> Result<Self, TablebaseError> {
+        if config.cells() > MAX_CELLS {
+            return Err(TablebaseError::TooLarge(config.cells()));
+        }
+        let mut analyzer = Analyzer::new();
+        let mut seen = HashSet::new();
+        let mut entries = vec![];
+        let mut stack = vec![BitBoard::new(config)];
+        while let Some(board) = stack.pop() {
+            let key = Self::key(&board);
+            if !seen.insert(key) {
+                continue;
+            }
+            entries.push((key, analyzer.evaluate(&board)));
+            if Analyzer::is_decided(&board) {
+                continue;
+            }
+            for index in board.legal_moves() {
+                let mut child = board.clone();
+                child.play(index);
+                stack.push(child);
+            }
+        }
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        Ok(Self { config, entries })
+    }
+
+    pub fn file_name(config: BoardConfig) -> String {
+        format!("{}x{}k{}.ttb", config.rows, config.cols, config.k)
+    }
+
+    pub fn config(&self) -> BoardConfig {
+        self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, board: &BitBoard) -> Option<GameValue> {
+        if board.config() != self.config {
+            return None;
+        }
+        let key = Self::key(board);
+        self.entries
+            .binary_search_by_key(&key, |&(key, _)| key)
+            .ok()
+            .map(|index| self.entries[index].1)
+    }
+
+    // the tablebase for `config`; a file for any other board is refused
+    pub fn load(path: impl AsRef<Path>, config: BoardConfig) -> Result<Self, TablebaseError> {
+        let tablebase = Self::read_from(BufReader::new(File::open(path)?))?;
+        if tablebase.config != config {
+            return Err(TablebaseError::WrongBoard {
+                expected: config,
+                found: tablebase.config,
+            });
+        }
+        Ok(tablebase)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), TablebaseError> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    // header: magic, version, rows, cols, k, entry count (u64 LE)
+    // entries: key (u64 LE) followed by one value byte
+    pub fn write_to(&self, mut writer: impl Write) -> Result<(), TablebaseError> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[
+            VERSION,
+            self.config.rows as u8,
+            self.config.cols as u8,
+            self.config.k as u8,
+        ])?;
+        writer.write_all(&(self.entries.len() as u64).to_le_bytes())?;
+        for &(key, value) in &self.entries {
+            writer.write_all(&key.to_le_bytes())?;
+            writer.write_all(&[encode_value(value)])?;
+        }
+        Ok(())
+    }
+
+    pub fn read_from(mut reader: impl Read) -> Result<Self, TablebaseError> {
+        let mut magic = [0; 4];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(TablebaseError::BadMagic);
+        }
+        let mut header = [0; 4];
+        reader.read_exact(&mut header)?;
+        let [version, rows, cols, k] = header;
+        if version != VERSION {
+            return Err(TablebaseError::BadVersion(version));
+        }
+        let config = BoardConfig::new(rows as usize, cols as usize, k as usize);
+        let mut count = [0; 8];
+        reader.read_exact(&mut count)?;
+        let count = u64::from_le_bytes(count) as usize;
+        let mut entries = Vec::with_capacity(count.min(1 << 24));
+        let mut entry = [0; 9];
+        for _ in 0..count {
+            reader.read_exact(&mut entry)?;
+            let key = u64::from_le_bytes(entry[..8].try_into().expect("8 byte key"));
+            entries.push((key, decode_value(entry[8])?));
+        }
+        entries.sort_unstable_by_key(|&(key, _)| key);
+        Ok(Self { config, entries })
+    }
+
+    fn key(board: &BitBoard) -> u64 {
+        board.relative_to_side().canonical_hash()
+    }
+}
+
+fn encode_value(value: GameValue) -> u8 {
+    match value {
+        GameValue::Draw => 0,
+        GameValue::Win(plies) => 0x40 | plies as u8,
+        GameValue::Loss(plies) => 0x80 | plies as u8,
+    }
+}
+
+fn decode_value(byte: u8) -> Result<GameValue, TablebaseError> {
+    let plies = (byte & 0x3F) as usize;
+    match byte >> 6 {
+        0 if plies == 0 => Ok(GameValue::Draw),
+        1 => Ok(GameValue::Win(plies)),
+        2 => Ok(GameValue::Loss(plies)),
+        _ => Err(TablebaseError::BadValue(byte)),
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{
+        TcpStream,
+        tcp::{OwnedReadHalf, OwnedWriteHalf},
+    },
+    sync::mpsc,
+};
+use tracing::info;
+
+use crate::wire::{self, Encoding, Hello, PROTOCOL_VERSION, WireError};
+
+// a frame larger than this is a broken or hostile peer
+pub const MAX_FRAME_LEN: usize = 1 << 20;
+
+#[derive(Debug, thiserror::Error)]
+pub enum TransportError {
+    #[error("Io error: {0:?}")]
+    IoError(#[from] std::io::Error),
+    #[error("Wire error: {0}")]
+    WireError(#[from] WireError),
+    #[error(
+        "Protocol version mismatch: the peer speaks version {theirs} but this build speaks \
+         version {ours}; update the older side"
+    )]
+    VersionMismatch { ours: u32, theirs: u32 },
+    #[error("The peer did not start with a hello; it may predate protocol versioning")]
+    NoHello,
+    #[error("Frame of {0} bytes is larger than allowed")]
+    FrameTooLarge(usize),
+}
+
+// forwards messages between a local channel pair and a TCP peer until
+// either side closes; the connection starts with a hello from each side,
+// which settles the encoding
+pub async fn bridge<In, Out>(
+    stream: TcpStream,
+    encoding: Encoding,
+    tx: mpsc::Sender<In>,
+    mut rx: mpsc::Receiver<Out>,
+) -> Result<(), TransportError>
+where
+    In: DeserializeOwned,
+    Out: Serialize,
+{
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+    let encoding = handshake(&mut reader, &mut writer, Hello::new(encoding)).await?;
+    // reading a frame is not cancel safe, so each direction runs on its own
+    // and the connection ends as soon as either does
+    let incoming = async {
+        while let Some(frame) = read_frame(&mut reader, encoding).await? {
+            if tx.send(wire::decode(encoding, &frame)?).await.is_err() {
+                break;
+            }
+        }
+        Ok::<(), TransportError>(())
+    };
+    let outgoing = async {
+        while let Some(message) = rx.recv().await {
+            write_frame(&mut writer, encoding, &wire::encode(encoding, &message)?).await?;
+        }
+        Ok::<(), TransportError>(())
+    };
+    tokio::select! {
+        result = incoming => result,
+        result = outgoing => result,
+    }
+}
+
+async fn handshake(
+    reader: &mut BufReader<OwnedReadHalf>,
+    writer: &mut OwnedWriteHalf,
+    hello: Hello,
+) -> Result<Encoding, TransportError> {
+    write_frame(
+        writer,
+        Encoding::Json,
+        &wire::encode(Encoding::Json, &hello)?,
+    )
+    .await?;
+    let Some(line) = read_frame(reader, Encoding::Json).await? else {
+        return Err(TransportError::NoHello);
+    };
+    let peer: Hello = wire::decode(Encoding::Json, &line).map_err(|_| TransportError::NoHello)?;
+    if peer.protocol_version != PROTOCOL_VERSION {
+        return Err(TransportError::VersionMismatch {
+            ours: PROTOCOL_VERSION,
+            theirs: peer.protocol_version,
+        });
+    }
+    let encoding = hello.agreed_encoding(&peer);
+    info!("speaking protocol version {PROTOCOL_VERSION} encoded as {encoding:?}");
+    Ok(encoding)
+}
+
+// None once the peer has closed the connection
+pub async fn read_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    encoding: Encoding,
+) -> Result<Option<Vec<u8>>, TransportError> {
+    match encoding {
+        Encoding::Json => {
+            // one byte over for the newline ending a frame of the largest size
+            let mut limited = (&mut *reader).take(MAX_FRAME_LEN as u64 + 1);
+            let mut line = vec![];
+            if limited.read_until(b'\n', &mut line).await? == 0 {
+                return Ok(None);
+            }
+            if line.len() > MAX_FRAME_LEN && !line.ends_with(b"\n") {
+                return Err(TransportError::FrameTooLarge(line.len()));
+            }
+            Ok(Some(line))
+        }
+        Encoding::Binary => {
+            let len = match reader.read_u32().await {
+                Ok(len) => len as usize,
+                Err(err) if err.kind() == std::io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+            if len > MAX_FRAME_LEN {
+                return Err(TransportError::FrameTooLarge(len));
+            }
+            let mut frame = vec![0; len];
+            reader.read_exact(&mut frame).await?;
+            Ok(Some(frame))
+        }
+    }
+}
+
+async fn write_frame(
+    writer: &mut OwnedWriteHalf,
+    encoding: Encoding,
+    frame: &[u8],
+) -> Result<(), TransportError> {
+    match encoding {
+        Encoding::Json => {
+            writer.write_all(frame).await?;
+            writer.write_all(b"\n").await?;
+        }
+        Encoding::Binary => {
+            writer.write_u32(frame.len() as u32).await?;
+            writer.write_all(frame).await?;
+        }
+    }
+    Ok(())
+}
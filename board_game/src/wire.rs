@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+// bumped whenever a message changes shape; peers on different versions
+// refuse to talk
+pub const PROTOCOL_VERSION: u32 = 3;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WireError {
+    #[error("Json error: {0}")]
+    JsonError(#[from] serde_json::Error),
+    #[error("Binary error: {0}")]
+    BinaryError(#[from] postcard::Error),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Encoding {
+    // one JSON object per line, easy to read in a packet dump
+    Json,
+    // postcard frames behind a four byte big-endian length
+    #[default]
+    Binary,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    // can read and write the binary encoding
+    Binary,
+    // something a newer peer offers that this build does not know
+    #[serde(other)]
+    Unknown,
+}
+
+// the first line each side sends, always as JSON so that any version can
+// read it
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Hello {
+    pub protocol_version: u32,
+    pub capabilities: Vec<Capability>,
+}
+
+impl Hello {
+    // the hello of this build, offering the binary encoding only when it
+    // is preferred
+    pub fn new(encoding: Encoding) -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: match encoding {
+                Encoding::Json => vec![],
+                Encoding::Binary => vec![Capability::Binary],
+            },
+        }
+    }
+
+    // binary when both sides offer it, JSON otherwise
+    pub fn agreed_encoding(&self, peer: &Hello) -> Encoding {
+        let binary = |hello: &Hello| hello.capabilities.contains(&Capability::Binary);
+        if binary(self) && binary(peer) {
+            Encoding::Binary
+        } else {
+            Encoding::Json
+        }
+    }
+}
+
+pub fn encode<T: Serialize>(encoding: Encoding, message: &T) -> Result<Vec<u8>, WireError> {
+    Ok(match encoding {
+        Encoding::Json => serde_json::to_vec(message)?,
+        Encoding::Binary => postcard::to_stdvec(message)?,
+    })
+}
+
+pub fn decode<T: DeserializeOwned>(encoding: Encoding, bytes: &[u8]) -> Result<T, WireError> {
+    Ok(match encoding {
+        Encoding::Json => serde_json::from_slice(bytes)?,
+        Encoding::Binary => postcard::from_bytes(bytes)?,
+    })
+}
@@ -0,0 +1,105 @@
+use board_game::{
+    analysis::{Analyzer, GameValue, Outcome},
+    bitboard::BitBoard,
+    board::BoardConfig,
+};
+
+// a 3x3 board after these moves, X first
+fn position(moves: &[(usize, usize)]) -> BitBoard {
+    let mut board = BitBoard::new(BoardConfig::new(3, 3, 3));
+    for &(row, col) in moves {
+        board.place(row, col).unwrap();
+    }
+    board
+}
+
+fn cells(board: &BitBoard, moves: &[(usize, usize)]) -> Vec<usize> {
+    moves
+        .iter()
+        .map(|&(row, col)| board.index(row, col))
+        .collect()
+}
+
+#[test]
+fn the_empty_board_is_a_draw_whatever_x_opens_with() {
+    let mut analyzer = Analyzer::new();
+    let board = position(&[]);
+    assert_eq!(analyzer.evaluate(&board), GameValue::Draw);
+    let best_moves = analyzer.best_moves(&board);
+    assert_eq!(best_moves.len(), 9);
+    assert!(best_moves.iter().all(|best| best.value == GameValue::Draw));
+    assert_eq!(analyzer.forced_win(&board), None);
+}
+
+#[test]
+fn a_line_that_can_be_completed_wins_in_one() {
+    let mut analyzer = Analyzer::new();
+    // X X . / O O . / . . .
+    let board = position(&[(0, 0), (1, 0), (0, 1), (1, 1)]);
+    assert_eq!(analyzer.evaluate(&board), GameValue::Win(1));
+    let best_moves = analyzer.best_moves(&board);
+    assert_eq!(best_moves.len(), 1);
+    assert_eq!((best_moves[0].row, best_moves[0].col), (0, 2));
+    assert_eq!(best_moves[0].value, GameValue::Win(1));
+    // anything else lets O complete the middle row
+    let analysis = analyzer.analyze(&board);
+    let corner = analysis
+        .iter()
+        .find(|analysis| (analysis.row, analysis.col) == (2, 2))
+        .unwrap();
+    assert_eq!(corner.value, GameValue::Loss(2));
+}
+
+#[test]
+fn a_double_threat_loses_in_two() {
+    let mut analyzer = Analyzer::new();
+    // X X . / X O . / . . O, with O to move and X threatening two lines
+    let board = position(&[(0, 0), (1, 1), (0, 1), (2, 2), (1, 0)]);
+    assert_eq!(analyzer.evaluate(&board), GameValue::Loss(2));
+    let best_moves = analyzer.best_moves(&board);
+    assert!(!best_moves.is_empty());
+    assert!(
+        best_moves
+            .iter()
+            .all(|best| best.value == GameValue::Loss(2))
+    );
+    assert_eq!(analyzer.forced_win(&board), None);
+}
+
+#[test]
+fn forced_win_finds_the_fork() {
+    let mut analyzer = Analyzer::new();
+    // X . O / . O . / . . X: X has to block at (2, 0), which makes two
+    // lines O cannot both stop
+    let board = position(&[(0, 0), (1, 1), (2, 2), (0, 2)]);
+    assert_eq!(analyzer.forced_win(&board), Some(3));
+    let best_moves = analyzer.best_moves(&board);
+    assert_eq!(best_moves.len(), 1);
+    assert_eq!((best_moves[0].row, best_moves[0].col), (2, 0));
+    assert_eq!(best_moves[0].value, GameValue::Win(3));
+
+    let mut board = board;
+    board.place(2, 0).unwrap();
+    assert_eq!(analyzer.evaluate(&board), GameValue::Loss(2));
+}
+
+#[test]
+fn review_flags_the_moves_that_gave_up_a_result() {
+    let mut analyzer = Analyzer::new();
+    let board = position(&[]);
+    // O's edge reply to a corner loses, and X lets the win go again with a
+    // move that only draws
+    let moves = cells(&board, &[(0, 0), (0, 1), (2, 1)]);
+    let reviews = analyzer.review(board.config(), 0, &moves).unwrap();
+
+    assert_eq!(reviews.len(), 3);
+    assert!(!reviews[0].is_blunder);
+    assert_eq!(reviews[1].player, 1);
+    assert_eq!(reviews[1].before.outcome(), Outcome::Draw);
+    assert_eq!(reviews[1].after.outcome(), Outcome::Loss);
+    assert!(reviews[1].is_blunder);
+    assert_eq!(reviews[2].player, 0);
+    assert_eq!(reviews[2].before.outcome(), Outcome::Win);
+    assert_eq!(reviews[2].after, GameValue::Draw);
+    assert!(reviews[2].is_blunder);
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use board_game::{
+    bitboard::{BitBoard, BitBoardError, symmetry::Symmetry, win_masks::WinMasks},
+    board::{Board, BoardConfig},
+    player::Player,
+    tile::Tile,
+};
+
+#[test]
+fn a_position_survives_the_trip_through_tiles() {
+    let mut board = BitBoard::new(BoardConfig::new(3, 4, 3));
+    for (row, col) in [(0, 0), (1, 1), (2, 3), (0, 3), (1, 0)] {
+        board.place(row, col).unwrap();
+    }
+    let tiles = Board::from(&board);
+    assert_eq!(tiles.owner_at(0, 0), Some(&Player { id: 0 }));
+    assert_eq!(tiles.owner_at(0, 3), Some(&Player { id: 1 }));
+    assert_eq!(tiles.owner_at(2, 2), None);
+
+    let back = BitBoard::try_from(&tiles).unwrap();
+    assert_eq!(back, board);
+    assert_eq!(back.hash(), board.hash());
+    assert_eq!(back.move_count(), 5);
+    assert_eq!(back.side_to_move(), 1);
+}
+
+#[test]
+fn tiles_that_make_no_board_are_refused() {
+    let mut tiles = Board::with_config(BoardConfig::new(3, 3, 3));
+    tiles.board[1].pop();
+    assert!(matches!(
+        BitBoard::try_from(&tiles),
+        Err(BitBoardError::Ragged)
+    ));
+
+    let mut tiles = Board::with_config(BoardConfig::new(3, 3, 3));
+    tiles.board[1][1] = Some(Tile {
+        owner: Some(Player { id: 2 }),
+    });
+    assert!(matches!(
+        BitBoard::try_from(&tiles),
+        Err(BitBoardError::UnknownPlayer(2))
+    ));
+}
+
+#[test]
+fn a_board_past_64_cells_spans_several_words() {
+    // 9x9, where row 7 starts at cell 63 and so crosses into the second word
+    let config = BoardConfig::new(9, 9, 5);
+    let mut board = BitBoard::new(config);
+    for col in 0..4 {
+        board.place(7, col).unwrap();
+        board.place(0, col).unwrap();
+    }
+    assert_eq!(board.winner(), None);
+    board.place(7, 4).unwrap();
+    assert_eq!(board.winner(), Some(0));
+    assert!(board.is_win_at(board.index(7, 4)));
+    assert_eq!(board.get(7, 0), Some(0));
+    assert_eq!(board.owner(80), None);
+
+    let mirrored = board.transformed(Symmetry::FlipVertical);
+    assert_eq!(mirrored.get(1, 0), Some(0));
+    assert_eq!(mirrored.winner(), Some(0));
+    assert_eq!(mirrored.canonical_hash(), board.canonical_hash());
+
+    let back = BitBoard::try_from(&Board::from(&board)).unwrap();
+    assert_eq!(back, board);
+    board.undo(board.index(7, 4));
+    assert_eq!(board.winner(), None);
+}
+
+#[test]
+fn win_masks_are_built_once_per_board() {
+    let config = BoardConfig::new(3, 6, 3);
+    assert!(Arc::ptr_eq(&WinMasks::get(config), &WinMasks::get(config)));
+    assert!(!Arc::ptr_eq(
+        &WinMasks::get(config),
+        &WinMasks::get(BoardConfig::new(6, 3, 3))
+    ));
+}
@@ -0,0 +1,157 @@
+// plays a game against a real manager and checks that its events rebuild
+// the game it ended with
+
+use std::sync::Arc;
+
+use board_game::{
+    board::BoardConfig,
+    event::GameEvent,
+    game_manager::{Game, GameManager},
+    message::{
+        Envelope, ExecutorToManagerMsg, ManagerToExecutorMsg,
+        request_message::{ExecutorToManagerReqMsg, ManagerToExecutorReqMsg},
+        response_message::{ExecutorToManagerResMsg, ManagerToExecutorResMsg},
+        snapshot::Outcome,
+    },
+    player::Player,
+};
+use tokio::sync::mpsc;
+
+// the executor's side of the channels, answering what the manager asks
+struct Peer {
+    tx: mpsc::Sender<Envelope<ExecutorToManagerMsg>>,
+    rx: mpsc::Receiver<Envelope<ManagerToExecutorMsg>>,
+    next_id: u64,
+    game_id: u64,
+}
+
+impl Peer {
+    async fn send(&mut self, correlation_id: u64, body: ExecutorToManagerMsg) {
+        let envelope = Envelope {
+            correlation_id,
+            sequence: 0,
+            game_id: self.game_id,
+            body,
+        };
+        self.tx.send(envelope).await.unwrap();
+    }
+
+    // the manager's next message that is not one of its requests, which are
+    // answered on the way; answering a quit request is left to the caller
+    async fn next(&mut self) -> Envelope<ManagerToExecutorMsg> {
+        loop {
+            let envelope = self.rx.recv().await.unwrap();
+            let response = match &envelope.body {
+                ManagerToExecutorMsg::Request(ManagerToExecutorReqMsg::InitGameRequest) => {
+                    ExecutorToManagerResMsg::InitGameResponse
+                }
+                ManagerToExecutorMsg::Request(ManagerToExecutorReqMsg::ExecuteGameRequest) => {
+                    ExecutorToManagerResMsg::ExecuteGameResponse
+                }
+                ManagerToExecutorMsg::Request(ManagerToExecutorReqMsg::PlayerWinRequest(..)) => {
+                    ExecutorToManagerResMsg::PlayerWinResponse
+                }
+                ManagerToExecutorMsg::Request(ManagerToExecutorReqMsg::SyncStateRequest(_)) => {
+                    continue;
+                }
+                _ => return envelope,
+            };
+            self.send(
+                envelope.correlation_id,
+                ExecutorToManagerMsg::Response(response),
+            )
+            .await;
+        }
+    }
+
+    async fn request(&mut self, request: ExecutorToManagerReqMsg) -> ManagerToExecutorMsg {
+        let correlation_id = self.next_id;
+        self.next_id += 1;
+        self.send(correlation_id, ExecutorToManagerMsg::Request(request))
+            .await;
+        let reply = self.next().await;
+        assert_eq!(reply.correlation_id, correlation_id);
+        self.game_id = reply.game_id;
+        reply.body
+    }
+
+    async fn play(&mut self, id: usize, row: usize, col: usize) -> ManagerToExecutorMsg {
+        self.request(ExecutorToManagerReqMsg::TileOnByPlayerRequesst(
+            Player { id },
+            row,
+            col,
+        ))
+        .await
+    }
+}
+
+#[tokio::test]
+async fn events_rebuild_the_game() {
+    let (manager_tx, rx) = mpsc::channel(32);
+    let (tx, manager_rx) = mpsc::channel(32);
+    let manager = Arc::new(GameManager::new().set_rx(manager_rx).set_tx(manager_tx));
+    let mut events = manager.subscribe();
+    let session = tokio::spawn(manager.clone().start());
+    let mut peer = Peer {
+        tx,
+        rx,
+        next_id: 1000,
+        game_id: 0,
+    };
+
+    peer.request(ExecutorToManagerReqMsg::ResetGameRequest(
+        BoardConfig::new(3, 3, 3),
+        Player { id: 0 },
+    ))
+    .await;
+    peer.play(0, 0, 0).await;
+    let rejected = peer.play(1, 0, 0).await;
+    assert!(matches!(
+        rejected,
+        ManagerToExecutorMsg::ErrorNotification(_)
+    ));
+    peer.play(1, 1, 1).await;
+    peer.request(ExecutorToManagerReqMsg::UndoMoveRequest(1, 1))
+        .await;
+    for (id, row, col) in [(1, 1, 0), (0, 0, 1), (1, 2, 0)] {
+        peer.play(id, row, col).await;
+    }
+    let won = peer.play(0, 0, 2).await;
+    assert!(matches!(
+        won,
+        ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::TileOnByPlayerResponse(true))
+    ));
+    let state = manager.game.lock().await.snapshot();
+
+    peer.send(
+        0,
+        ExecutorToManagerMsg::Request(ExecutorToManagerReqMsg::ReadyToQuitGameRequest),
+    )
+    .await;
+    let quit = peer.next().await;
+    assert!(matches!(
+        quit.body,
+        ManagerToExecutorMsg::Request(ManagerToExecutorReqMsg::QuitGameRequest)
+    ));
+    peer.send(
+        quit.correlation_id,
+        ExecutorToManagerMsg::Response(ExecutorToManagerResMsg::QuitGameResponse),
+    )
+    .await;
+    session.await.unwrap().unwrap();
+
+    let mut log = vec![];
+    while let Ok(event) = events.try_recv() {
+        log.push(event);
+    }
+    assert_eq!(Game::replay(&log).snapshot(), state);
+    assert!(
+        log.iter()
+            .any(|event| matches!(event, GameEvent::MoveRejected { .. }))
+    );
+    assert!(log.contains(&GameEvent::GameEnded(Outcome::Won(
+        Player { id: 0 },
+        vec![(0, 0), (0, 1), (0, 2)]
+    ))));
+    assert_eq!(log.last(), Some(&GameEvent::PlayerLeft(Player { id: 1 })));
+}
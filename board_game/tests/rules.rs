@@ -0,0 +1,184 @@
+// properties of the rules over random legal games on random boards, up to
+// 10x10 so that boards past 64 cells are covered too
+
+use board_game::{
+    analysis::Analyzer,
+    bitboard::BitBoard,
+    board::{Board, BoardConfig},
+    consts::PLAYER_NUM,
+    event::GameEvent,
+    game_manager::Game,
+    message::snapshot::Outcome,
+    player::Player,
+    record::{GameRecord, RecordError},
+};
+use proptest::{collection::vec, prelude::*, sample::Index};
+
+// enough picks to fill the largest board
+const MAX_MOVES: usize = 100;
+
+fn config() -> impl Strategy<Value = BoardConfig> {
+    (1..=10usize, 1..=10usize)
+        .prop_flat_map(|(rows, cols)| (Just(rows), Just(cols), 1..=rows.max(cols)))
+        .prop_map(|(rows, cols, k)| BoardConfig::new(rows, cols, k))
+}
+
+// a board, who opens, and one pick among the legal moves for every turn
+fn game() -> impl Strategy<Value = (BoardConfig, usize, Vec<Index>)> {
+    (config(), 0..PLAYER_NUM, vec(any::<Index>(), 0..=MAX_MOVES))
+}
+
+// the standard rules: legal moves until a side has a line or the board is
+// full, or until the picks run out
+fn play_out(config: BoardConfig, first_player: usize, picks: &[Index]) -> Vec<usize> {
+    let mut board = BitBoard::new(config);
+    board.set_side_to_move(first_player);
+    let mut moves = vec![];
+    for pick in picks {
+        if Analyzer::is_decided(&board) {
+            break;
+        }
+        let legal = board.legal_moves();
+        let index = legal[pick.index(legal.len())];
+        board.play(index);
+        moves.push(index);
+    }
+    moves
+}
+
+// the same moves played into the game the manager keeps
+fn manager_game(positions: &[BitBoard], first_player: usize, moves: &[usize]) -> Game {
+    let mut game = Game::new(positions[0].config(), Player { id: first_player });
+    for (board, &index) in positions.iter().zip(moves) {
+        let (row, col) = board.position(index);
+        game.apply(&GameEvent::MovePlayed {
+            player: Player {
+                id: board.side_to_move(),
+            },
+            row,
+            col,
+        });
+    }
+    game
+}
+
+proptest! {
+    #[test]
+    fn an_unplayable_board_is_refused(
+        rows in any::<usize>(),
+        cols in any::<usize>(),
+        k in any::<usize>(),
+        moves in vec(any::<usize>(), 0..4),
+    ) {
+        let config = BoardConfig::new(rows, cols, k);
+        prop_assume!(!config.is_playable());
+        let refused = GameRecord::new(config, 0, moves).positions();
+        prop_assert!(matches!(refused, Err(RecordError::UnplayableBoard(_))));
+    }
+
+    #[test]
+    fn marks_never_exceed_cells((config, first_player, picks) in game()) {
+        let moves = play_out(config, first_player, &picks);
+        let positions = GameRecord::new(config, first_player, moves).positions().unwrap();
+        for board in &positions {
+            prop_assert!(board.move_count() <= config.cells());
+            prop_assert_eq!(board.marked_cells().count(), board.move_count());
+            prop_assert_eq!(board.legal_moves().len(), config.cells() - board.move_count());
+        }
+    }
+
+    #[test]
+    fn a_decided_game_accepts_no_more_moves(
+        config in config(),
+        first_player in 0..PLAYER_NUM,
+        picks in vec(any::<Index>(), MAX_MOVES),
+        extra in 0..MAX_MOVES,
+    ) {
+        let mut moves = play_out(config, first_player, &picks);
+        let positions = GameRecord::new(config, first_player, moves.clone())
+            .positions()
+            .unwrap();
+        let last = positions.last().unwrap();
+        prop_assert!(Analyzer::is_decided(last));
+        // the manager sees the same ending and so refuses every move too
+        let game = manager_game(&positions, first_player, &moves);
+        match (game.outcome(), last.winner()) {
+            (Outcome::Won(player, _), Some(winner)) => prop_assert_eq!(player.id, winner),
+            (Outcome::Draw, None) => prop_assert!(last.is_full()),
+            (outcome, winner) => prop_assert!(false, "{:?} but winner {:?}", outcome, winner),
+        }
+
+        let played = moves.len();
+        moves.push(extra);
+        let refused = GameRecord::new(config, first_player, moves).positions();
+        prop_assert!(matches!(
+            refused,
+            Err(RecordError::IllegalMove(number, index)) if number == played + 1 && index == extra
+        ));
+    }
+
+    #[test]
+    fn at_most_one_player_has_a_line((config, first_player, picks) in game()) {
+        let moves = play_out(config, first_player, &picks);
+        let positions = GameRecord::new(config, first_player, moves.clone()).positions().unwrap();
+        for (number, board) in positions.iter().enumerate() {
+            let winners: Vec<_> = (0..PLAYER_NUM)
+                .filter(|&player| board.has_won(player))
+                .collect();
+            prop_assert!(winners.len() <= 1);
+            // only the move just played can have made a line
+            if let Some(&winner) = winners.first() {
+                prop_assert_eq!(number, positions.len() - 1);
+                prop_assert_eq!(board.owner(moves[number - 1]), Some(winner));
+            }
+        }
+    }
+
+    #[test]
+    fn a_board_survives_the_trip_through_tiles((config, first_player, picks) in game()) {
+        let moves = play_out(config, first_player, &picks);
+        let positions = GameRecord::new(config, first_player, moves).positions().unwrap();
+        let board = positions.last().unwrap();
+        let mut back = BitBoard::try_from(&Board::from(board)).unwrap();
+        // tiles don't say who opened, so the side to move is a guess
+        back.set_side_to_move(board.side_to_move());
+        prop_assert_eq!(&back, board);
+        prop_assert_eq!(back.hash(), board.hash());
+        prop_assert_eq!(back.move_count(), board.move_count());
+        prop_assert_eq!(back.winner(), board.winner());
+    }
+
+    #[test]
+    fn undo_then_redo_is_the_identity(
+        (config, first_player, picks) in game(),
+        depth in any::<Index>(),
+    ) {
+        let moves = play_out(config, first_player, &picks);
+        let positions = GameRecord::new(config, first_player, moves.clone()).positions().unwrap();
+        let taken_back = &moves[moves.len() - depth.index(moves.len() + 1)..];
+
+        let mut board = positions.last().unwrap().clone();
+        for &index in taken_back.iter().rev() {
+            board.undo(index);
+        }
+        prop_assert_eq!(&board, &positions[moves.len() - taken_back.len()]);
+        for &index in taken_back {
+            board.play(index);
+        }
+        prop_assert_eq!(&board, positions.last().unwrap());
+        prop_assert_eq!(board.hash(), positions.last().unwrap().hash());
+
+        let mut game = manager_game(&positions, first_player, &moves);
+        let before = game.snapshot();
+        for &index in taken_back.iter().rev() {
+            let (row, col) = board.position(index);
+            game.apply(&GameEvent::MoveUndone { row, col });
+        }
+        for &index in taken_back {
+            let (row, col) = board.position(index);
+            let player = game.current_player();
+            game.apply(&GameEvent::MovePlayed { player, row, col });
+        }
+        prop_assert_eq!(game.snapshot(), before);
+    }
+}
@@ -0,0 +1,645 @@
+// runs a real manager against a scripted executor on a paused clock, so
+// timeouts pass instantly and every run schedules the same way; the tests
+// pin the exact messages of the protocol and poke at it with delays and
+// messages delivered out of their usual order
+
+use std::{sync::Arc, time::Duration};
+
+use board_game::{
+    board::BoardConfig,
+    event::GameEvent,
+    game_manager::{Game, GameManager, GameManagerError},
+    message::{
+        Envelope, ExecutorToManagerMsg, ManagerToExecutorMsg,
+        notification::ErrorNotification,
+        request_message::{ExecutorToManagerReqMsg, ManagerToExecutorReqMsg},
+        response_message::{ExecutorToManagerResMsg, ManagerToExecutorResMsg},
+        snapshot::Outcome,
+    },
+    outbox::{OutboxError, REQUEST_TIMEOUT},
+    player::Player,
+    shutdown::SHUTDOWN_DEADLINE,
+};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{Instant, sleep, timeout},
+};
+
+// longer than anything in the protocol waits, so a manager that says nothing
+// for this long has nothing to say
+const PATIENCE: Duration = Duration::from_secs(60);
+
+type Incoming = Envelope<ManagerToExecutorMsg>;
+
+// the executor's side of a session, driven step by step by a test
+struct Sim {
+    manager: Arc<GameManager>,
+    session: JoinHandle<Result<(), GameManagerError>>,
+    tx: mpsc::Sender<Envelope<ExecutorToManagerMsg>>,
+    rx: mpsc::Receiver<Incoming>,
+    next_id: u64,
+    // what the manager's next envelope has to carry
+    sequence: u64,
+    game_id: u64,
+    // the game as the executor expects the manager to have it
+    mirror: Game,
+}
+
+impl Sim {
+    fn start() -> Self {
+        let (manager_tx, rx) = mpsc::channel(32);
+        let (tx, manager_rx) = mpsc::channel(32);
+        let manager = Arc::new(GameManager::new().set_rx(manager_rx).set_tx(manager_tx));
+        let session = tokio::spawn(manager.clone().start());
+        Self {
+            manager,
+            session,
+            tx,
+            rx,
+            next_id: 1000,
+            sequence: 0,
+            game_id: 0,
+            mirror: Game::default(),
+        }
+    }
+
+    // the manager's next message, if it sends one in time
+    async fn next(&mut self) -> Option<Incoming> {
+        let envelope = timeout(PATIENCE, self.rx.recv()).await.ok()??;
+        assert_eq!(envelope.sequence, self.sequence, "a message was lost");
+        self.sequence += 1;
+        assert_eq!(envelope.game_id, self.game_id);
+        Some(envelope)
+    }
+
+    async fn expect(&mut self, body: ManagerToExecutorMsg) -> Incoming {
+        let envelope = self.next().await.expect("the manager went quiet");
+        assert_eq!(envelope.body, body);
+        envelope
+    }
+
+    // the ID to answer the request with
+    async fn expect_request(&mut self, request: ManagerToExecutorReqMsg) -> u64 {
+        self.expect(ManagerToExecutorMsg::Request(request))
+            .await
+            .correlation_id
+    }
+
+    async fn expect_reply(&mut self, correlation_id: u64, body: ManagerToExecutorMsg) {
+        let reply = self.expect(body).await;
+        assert_eq!(reply.correlation_id, correlation_id);
+    }
+
+    // the board the manager has to push after a change
+    async fn expect_sync(&mut self) {
+        let snapshot = self.mirror.snapshot();
+        self.expect_request(ManagerToExecutorReqMsg::SyncStateRequest(snapshot))
+            .await;
+    }
+
+    async fn expect_silence(&mut self) {
+        if let Ok(envelope) = timeout(PATIENCE, self.rx.recv()).await {
+            panic!("expected nothing, got {envelope:?}");
+        }
+    }
+
+    // the next `count` messages, in whatever order they come
+    async fn collect(&mut self, count: usize) -> Vec<Incoming> {
+        let mut envelopes = vec![];
+        for _ in 0..count {
+            envelopes.push(self.next().await.expect("the manager went quiet"));
+        }
+        envelopes
+    }
+
+    // stamped now, delivered whenever the test likes
+    fn envelope(&mut self, request: ExecutorToManagerReqMsg) -> Envelope<ExecutorToManagerMsg> {
+        let correlation_id = self.next_id;
+        self.next_id += 1;
+        Envelope {
+            correlation_id,
+            sequence: 0,
+            game_id: self.game_id,
+            body: ExecutorToManagerMsg::Request(request),
+        }
+    }
+
+    async fn deliver(&self, envelope: Envelope<ExecutorToManagerMsg>) {
+        self.tx.send(envelope).await.unwrap();
+    }
+
+    async fn request(&mut self, request: ExecutorToManagerReqMsg) -> u64 {
+        let envelope = self.envelope(request);
+        let correlation_id = envelope.correlation_id;
+        self.deliver(envelope).await;
+        correlation_id
+    }
+
+    async fn respond(&self, correlation_id: u64, response: ExecutorToManagerResMsg) {
+        self.deliver(Envelope {
+            correlation_id,
+            sequence: 0,
+            game_id: self.game_id,
+            body: ExecutorToManagerMsg::Response(response),
+        })
+        .await;
+    }
+
+    async fn handshake(&mut self) {
+        let init = self
+            .expect_request(ManagerToExecutorReqMsg::InitGameRequest)
+            .await;
+        self.respond(init, ExecutorToManagerResMsg::InitGameResponse)
+            .await;
+        let execute = self
+            .expect_request(ManagerToExecutorReqMsg::ExecuteGameRequest)
+            .await;
+        self.respond(execute, ExecutorToManagerResMsg::ExecuteGameResponse)
+            .await;
+    }
+
+    async fn reset(&mut self, config: BoardConfig, first_player: usize) {
+        let first_player = Player { id: first_player };
+        let correlation_id = self
+            .request(ExecutorToManagerReqMsg::ResetGameRequest(
+                config,
+                first_player.clone(),
+            ))
+            .await;
+        self.game_id += 1;
+        self.mirror = Game::new(config, first_player);
+        self.expect_reply(
+            correlation_id,
+            ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::ResetGameResponse),
+        )
+        .await;
+    }
+
+    // sends a move and takes it into the mirror; the replies are left to the
+    // caller
+    async fn send_move(&mut self, id: usize, row: usize, col: usize) -> u64 {
+        self.mirror.apply(&GameEvent::MovePlayed {
+            player: Player { id },
+            row,
+            col,
+        });
+        self.request(ExecutorToManagerReqMsg::TileOnByPlayerRequesst(
+            Player { id },
+            row,
+            col,
+        ))
+        .await
+    }
+
+    // a legal move that does not win
+    async fn play(&mut self, id: usize, row: usize, col: usize) {
+        let correlation_id = self.send_move(id, row, col).await;
+        self.expect_sync().await;
+        self.expect_reply(correlation_id, tile_on(false)).await;
+    }
+
+    // X takes the top row on a fresh 3x3 board, all but the last move
+    async fn play_to_the_brink(&mut self) {
+        self.reset(BoardConfig::new(3, 3, 3), 0).await;
+        for (id, row, col) in [(0, 0, 0), (1, 1, 0), (0, 0, 1), (1, 2, 0)] {
+            self.play(id, row, col).await;
+        }
+    }
+
+    async fn quit(mut self) -> Result<(), GameManagerError> {
+        self.request(ExecutorToManagerReqMsg::ReadyToQuitGameRequest)
+            .await;
+        let quit = self
+            .expect_request(ManagerToExecutorReqMsg::QuitGameRequest)
+            .await;
+        self.respond(quit, ExecutorToManagerResMsg::QuitGameResponse)
+            .await;
+        self.session.await.unwrap()
+    }
+}
+
+fn tile_on(is_win: bool) -> ManagerToExecutorMsg {
+    ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::TileOnByPlayerResponse(is_win))
+}
+
+fn refusal(row: usize, col: usize) -> ManagerToExecutorMsg {
+    ManagerToExecutorMsg::ErrorNotification(ErrorNotification {
+        message: format!("Cannot place a mark at ({row}, {col})"),
+        fatal: false,
+    })
+}
+
+fn top_row_win() -> ManagerToExecutorReqMsg {
+    ManagerToExecutorReqMsg::PlayerWinRequest(Player { id: 0 }, vec![(0, 0), (0, 1), (0, 2)])
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_game_runs_from_handshake_to_quit() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.play_to_the_brink().await;
+    // the win is announced before the move is answered
+    let correlation_id = sim.send_move(0, 0, 2).await;
+    let win = sim.expect_request(top_row_win()).await;
+    sim.respond(win, ExecutorToManagerResMsg::PlayerWinResponse)
+        .await;
+    sim.expect_sync().await;
+    sim.expect_reply(correlation_id, tile_on(true)).await;
+    assert!(matches!(sim.mirror.outcome(), Outcome::Won(..)));
+    sim.quit().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_drawn_game_and_the_next_one_share_a_session() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 1).await;
+    // O X O / O X X / X O O, with X never three in a row
+    for (id, row, col) in [
+        (1, 0, 0),
+        (0, 0, 1),
+        (1, 0, 2),
+        (0, 1, 1),
+        (1, 2, 1),
+        (0, 1, 2),
+        (1, 1, 0),
+        (0, 2, 0),
+        (1, 2, 2),
+    ] {
+        sim.play(id, row, col).await;
+    }
+    assert_eq!(sim.mirror.outcome(), Outcome::Draw);
+    let correlation_id = sim.send_move(0, 0, 0).await;
+    sim.expect_reply(correlation_id, refusal(0, 0)).await;
+    sim.reset(BoardConfig::new(4, 4, 4), 0).await;
+    assert_eq!(sim.game_id, 2);
+    sim.play(0, 3, 3).await;
+    sim.quit().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn the_game_is_executed_only_after_init_is_answered() {
+    let mut sim = Sim::start();
+    let init = sim
+        .expect_request(ManagerToExecutorReqMsg::InitGameRequest)
+        .await;
+    sim.expect_silence().await;
+    sim.respond(init, ExecutorToManagerResMsg::InitGameResponse)
+        .await;
+    sim.expect_request(ManagerToExecutorReqMsg::ExecuteGameRequest)
+        .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_move_sent_twice_is_played_once() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    let move_ = ExecutorToManagerReqMsg::TileOnByPlayerRequesst(Player { id: 0 }, 1, 1);
+    let first = sim.envelope(move_.clone());
+    let retry = sim.envelope(move_);
+    let ids = [first.correlation_id, retry.correlation_id];
+    // both are in the queue before the manager looks at either
+    sim.deliver(first).await;
+    sim.deliver(retry).await;
+    sim.mirror.apply(&GameEvent::MovePlayed {
+        player: Player { id: 0 },
+        row: 1,
+        col: 1,
+    });
+
+    let replies = sim.collect(3).await;
+    let sync = ManagerToExecutorMsg::Request(ManagerToExecutorReqMsg::SyncStateRequest(
+        sim.mirror.snapshot(),
+    ));
+    assert_eq!(replies.iter().filter(|reply| reply.body == sync).count(), 1);
+    let answers: Vec<_> = replies
+        .iter()
+        .filter(|reply| ids.contains(&reply.correlation_id))
+        .map(|reply| &reply.body)
+        .collect();
+    assert_eq!(answers.len(), 2);
+    assert!(answers.contains(&&tile_on(false)));
+    assert!(answers.contains(&&refusal(1, 1)));
+    assert_eq!(
+        sim.manager.game.lock().await.snapshot(),
+        sim.mirror.snapshot()
+    );
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_move_out_of_turn_is_refused() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    let mut events = sim.manager.subscribe();
+    sim.play(0, 1, 1).await;
+    let correlation_id = sim
+        .request(ExecutorToManagerReqMsg::TileOnByPlayerRequesst(
+            Player { id: 0 },
+            0,
+            0,
+        ))
+        .await;
+    sim.expect_reply(correlation_id, refusal(0, 0)).await;
+    assert_eq!(
+        sim.manager.game.lock().await.snapshot(),
+        sim.mirror.snapshot()
+    );
+    // rejected like any other illegal move, so the metrics count it
+    let mut rejected = vec![];
+    while let Ok(event) = events.try_recv() {
+        if let GameEvent::MoveRejected {
+            player, row, col, ..
+        } = event
+        {
+            rejected.push((player.id, row, col));
+        }
+    }
+    assert_eq!(rejected, [(0, 0, 0)]);
+    sim.play(1, 0, 0).await;
+    sim.quit().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn pipelined_moves_all_land_and_the_last_sync_is_current() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(4, 4, 4), 0).await;
+    let mut ids = vec![];
+    for (id, row, col) in [(0, 0, 0), (1, 3, 3), (0, 1, 2)] {
+        ids.push(sim.send_move(id, row, col).await);
+    }
+
+    let replies = sim.collect(6).await;
+    for id in ids {
+        let answers: Vec<_> = replies
+            .iter()
+            .filter(|reply| reply.correlation_id == id)
+            .collect();
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers[0].body, tile_on(false));
+    }
+    let last_sync = replies.iter().rev().find_map(|reply| match &reply.body {
+        ManagerToExecutorMsg::Request(ManagerToExecutorReqMsg::SyncStateRequest(snapshot)) => {
+            Some(snapshot)
+        }
+        _ => None,
+    });
+    assert_eq!(last_sync, Some(&sim.mirror.snapshot()));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_move_slipped_in_before_the_win_reply_is_refused() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.play_to_the_brink().await;
+    let winning = sim.send_move(0, 0, 2).await;
+    let win = sim.expect_request(top_row_win()).await;
+    // the game ended before the win went out, however long its reply takes
+    let late = sim
+        .request(ExecutorToManagerReqMsg::TileOnByPlayerRequesst(
+            Player { id: 1 },
+            2,
+            2,
+        ))
+        .await;
+    sim.expect_reply(late, refusal(2, 2)).await;
+    sim.respond(win, ExecutorToManagerResMsg::PlayerWinResponse)
+        .await;
+    sim.expect_sync().await;
+    sim.expect_reply(winning, tile_on(true)).await;
+    sim.quit().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_move_from_before_a_reset_is_dropped() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    let stale = sim.envelope(ExecutorToManagerReqMsg::TileOnByPlayerRequesst(
+        Player { id: 0 },
+        1,
+        1,
+    ));
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    sim.deliver(stale).await;
+    sim.expect_silence().await;
+
+    let correlation_id = sim.request(ExecutorToManagerReqMsg::GetStateRequest).await;
+    let snapshot = sim.mirror.snapshot();
+    assert_eq!(snapshot.move_number, 0);
+    sim.expect_reply(
+        correlation_id,
+        ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::StateSnapshotResponse(snapshot)),
+    )
+    .await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_reset_to_an_unsupported_board_is_refused() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    sim.play(0, 1, 1).await;
+    for config in [
+        BoardConfig::new(usize::MAX, usize::MAX, 3),
+        BoardConfig::new(3, 3, 0),
+        BoardConfig::new(10, 10, 5),
+    ] {
+        let correlation_id = sim
+            .request(ExecutorToManagerReqMsg::ResetGameRequest(
+                config,
+                Player { id: 0 },
+            ))
+            .await;
+        sim.expect_reply(
+            correlation_id,
+            ManagerToExecutorMsg::ErrorNotification(ErrorNotification {
+                message: format!("Cannot play on a {config} board"),
+                fatal: false,
+            }),
+        )
+        .await;
+    }
+    // the game that was on goes on
+    sim.play(1, 0, 0).await;
+    assert_eq!(
+        sim.manager.game.lock().await.snapshot(),
+        sim.mirror.snapshot()
+    );
+    sim.quit().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn only_the_last_move_can_be_undone() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    for (id, row, col) in [(0, 1, 1), (1, 0, 0), (0, 2, 2)] {
+        sim.play(id, row, col).await;
+    }
+    let correlation_id = sim
+        .request(ExecutorToManagerReqMsg::UndoMoveRequest(0, 0))
+        .await;
+    sim.expect_reply(
+        correlation_id,
+        ManagerToExecutorMsg::ErrorNotification(ErrorNotification {
+            message: "Cannot take back the mark at (0, 0); only the last move can be".to_string(),
+            fatal: false,
+        }),
+    )
+    .await;
+    assert_eq!(
+        sim.manager.game.lock().await.snapshot(),
+        sim.mirror.snapshot()
+    );
+
+    let correlation_id = sim
+        .request(ExecutorToManagerReqMsg::UndoMoveRequest(2, 2))
+        .await;
+    sim.mirror.apply(&GameEvent::MoveUndone { row: 2, col: 2 });
+    sim.expect_sync().await;
+    sim.expect_reply(
+        correlation_id,
+        ManagerToExecutorMsg::Response(ManagerToExecutorResMsg::UndoMoveResponse),
+    )
+    .await;
+    sim.play(0, 0, 2).await;
+    sim.quit().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_win_reply_just_in_time_is_taken() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.play_to_the_brink().await;
+    let winning = sim.send_move(0, 0, 2).await;
+    let win = sim.expect_request(top_row_win()).await;
+    sleep(REQUEST_TIMEOUT - Duration::from_millis(1)).await;
+    sim.respond(win, ExecutorToManagerResMsg::PlayerWinResponse)
+        .await;
+    sim.expect_sync().await;
+    sim.expect_reply(winning, tile_on(true)).await;
+    sim.quit().await.unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn an_unanswered_win_ends_the_session() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.play_to_the_brink().await;
+    sim.send_move(0, 0, 2).await;
+    let win = sim.expect_request(top_row_win()).await;
+    let asked = Instant::now();
+
+    let failure = sim.next().await.unwrap();
+    assert_eq!(asked.elapsed(), REQUEST_TIMEOUT);
+    assert_eq!(
+        failure.body,
+        ManagerToExecutorMsg::ErrorNotification(ErrorNotification {
+            message: format!("Outbox error: No reply to request {win} in time"),
+            fatal: true,
+        })
+    );
+    // a reply that comes too late changes nothing
+    sim.respond(win, ExecutorToManagerResMsg::PlayerWinResponse)
+        .await;
+    let quit = sim
+        .expect_request(ManagerToExecutorReqMsg::QuitGameRequest)
+        .await;
+    sim.respond(quit, ExecutorToManagerResMsg::QuitGameResponse)
+        .await;
+    let result = sim.session.await.unwrap();
+    assert!(matches!(
+        result,
+        Err(GameManagerError::OutboxError(OutboxError::Timeout(id))) if id == win
+    ));
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_cancelled_session_waits_for_the_quit_reply_until_the_deadline() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    let cancelled = Instant::now();
+    sim.manager.cancel_token.cancel();
+    sim.expect_request(ManagerToExecutorReqMsg::QuitGameRequest)
+        .await;
+    sim.session.await.unwrap().unwrap();
+    assert_eq!(cancelled.elapsed(), SHUTDOWN_DEADLINE);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_slow_quit_reply_before_the_deadline_ends_the_session_cleanly() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    let cancelled = Instant::now();
+    sim.manager.cancel_token.cancel();
+    let quit = sim
+        .expect_request(ManagerToExecutorReqMsg::QuitGameRequest)
+        .await;
+    let delay = SHUTDOWN_DEADLINE - Duration::from_millis(1);
+    sleep(delay).await;
+    sim.respond(quit, ExecutorToManagerResMsg::QuitGameResponse)
+        .await;
+    sim.session.await.unwrap().unwrap();
+    assert_eq!(cancelled.elapsed(), delay);
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_second_ready_to_quit_asks_only_once() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.request(ExecutorToManagerReqMsg::ReadyToQuitGameRequest)
+        .await;
+    sim.request(ExecutorToManagerReqMsg::ReadyToQuitGameRequest)
+        .await;
+    let quit = sim
+        .expect_request(ManagerToExecutorReqMsg::QuitGameRequest)
+        .await;
+    sim.expect_silence().await;
+    sim.respond(quit, ExecutorToManagerResMsg::QuitGameResponse)
+        .await;
+    sim.session.await.unwrap().unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_vanished_executor_ends_the_session() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    sim.play(0, 1, 1).await;
+    let Sim { tx, session, .. } = sim;
+    drop(tx);
+    session.await.unwrap().unwrap();
+}
+
+#[tokio::test(start_paused = true)]
+async fn a_request_answered_in_place_that_fails_still_winds_the_session_down() {
+    let mut sim = Sim::start();
+    sim.handshake().await;
+    sim.reset(BoardConfig::new(3, 3, 3), 0).await;
+    let mut events = sim.manager.subscribe();
+    let envelope = sim.envelope(ExecutorToManagerReqMsg::GetStateRequest);
+    // the state can't be sent back to an executor that stopped listening
+    let Sim {
+        manager,
+        session,
+        tx,
+        rx,
+        ..
+    } = sim;
+    drop(rx);
+    tx.send(envelope).await.unwrap();
+    let result = session.await.unwrap();
+    assert!(matches!(result, Err(GameManagerError::OutboxError(_))));
+    assert!(manager.cancel_token.is_cancelled());
+    let mut left = vec![];
+    while let Ok(event) = events.try_recv() {
+        if let GameEvent::PlayerLeft(player) = event {
+            left.push(player.id);
+        }
+    }
+    assert_eq!(left, [0, 1]);
+}
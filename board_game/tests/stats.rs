@@ -0,0 +1,50 @@
+use std::{env, fs, process};
+
+use board_game::stats::{GameResult, Statistics, StatsStore, Streak};
+
+fn result(players: [&str; 2], winner: Option<usize>) -> GameResult {
+    GameResult {
+        players: players.map(String::from),
+        variant: "3x3 k=3".to_string(),
+        winner,
+        moves: 7,
+        duration_secs: 12.0,
+        finished_at: 1_700_000_000,
+    }
+}
+
+#[test]
+fn corrupt_lines_are_skipped() {
+    let path = env::temp_dir().join(format!("{}-stats.jsonl", process::id()));
+    let store = StatsStore::new(&path);
+    let first = result(["Ann", "Bob"], Some(0));
+    let second = result(["Bob", "Ann"], None);
+    store.record(&first).unwrap();
+    // a write cut short and a line mangled on disk
+    let mut contents = fs::read(&path).unwrap();
+    contents.extend_from_slice(b"{\"players\":[\"Ann\",\n\xff\xfe not json\n\n");
+    fs::write(&path, contents).unwrap();
+    store.record(&second).unwrap();
+
+    let loaded = store.load();
+    store.reset().unwrap();
+    assert_eq!(loaded.unwrap(), [first, second]);
+}
+
+#[test]
+fn a_game_against_the_same_name_counts_once() {
+    let results = [
+        result(["Player", "Player"], Some(1)),
+        result(["Player", "Player"], None),
+        result(["Player", "Bob"], Some(0)),
+    ];
+    let statistics = Statistics::from_results(&results);
+
+    let player = &statistics.players["Player"];
+    assert_eq!((player.wins, player.draws, player.losses), (1, 1, 1));
+    assert_eq!(player.games(), results.len());
+    assert_eq!(player.total_moves, 7 * results.len());
+    assert_eq!(player.current_streak, Some(Streak::Wins(1)));
+    let bob = &statistics.players["Bob"];
+    assert_eq!((bob.wins, bob.draws, bob.losses), (0, 0, 1));
+}
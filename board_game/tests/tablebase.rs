@@ -0,0 +1,136 @@
+use std::{collections::HashSet, env, fs, path::PathBuf, process};
+
+use board_game::{
+    analysis::{Analyzer, GameValue},
+    bitboard::BitBoard,
+    board::BoardConfig,
+    tablebase::{Tablebase, TablebaseError},
+};
+
+fn reachable_positions(config: BoardConfig) -> Vec<BitBoard> {
+    let mut seen = HashSet::new();
+    let mut positions = vec![];
+    let mut stack = vec![BitBoard::new(config)];
+    while let Some(board) = stack.pop() {
+        if !seen.insert(board.hash()) {
+            continue;
+        }
+        if !Analyzer::is_decided(&board) {
+            for index in board.legal_moves() {
+                let mut child = board.clone();
+                child.play(index);
+                stack.push(child);
+            }
+        }
+        positions.push(board);
+    }
+    positions
+}
+
+// a file of this test run's own, so parallel runs do not clash
+fn scratch_path(config: BoardConfig) -> PathBuf {
+    env::temp_dir().join(format!(
+        "{}-{}",
+        process::id(),
+        Tablebase::file_name(config)
+    ))
+}
+
+#[test]
+fn tablebase_matches_live_minimax() {
+    let config = BoardConfig::new(3, 3, 3);
+    let tablebase = Tablebase::generate(config).unwrap();
+    let positions = reachable_positions(config);
+    assert_eq!(positions.len(), 5478);
+    // positions equivalent under symmetry share one entry
+    assert_eq!(tablebase.len(), 765);
+
+    for board in positions {
+        let expected = Analyzer::new().evaluate(&board);
+        assert_eq!(tablebase.get(&board), Some(expected));
+    }
+}
+
+#[test]
+fn tablebase_round_trips_through_bytes() {
+    let config = BoardConfig::new(3, 3, 3);
+    let tablebase = Tablebase::generate(config).unwrap();
+    let mut bytes = vec![];
+    tablebase.write_to(&mut bytes).unwrap();
+    let loaded = Tablebase::read_from(bytes.as_slice()).unwrap();
+
+    assert_eq!(loaded.config(), config);
+    assert_eq!(loaded.len(), tablebase.len());
+    for board in reachable_positions(config) {
+        assert_eq!(loaded.get(&board), tablebase.get(&board));
+    }
+}
+
+#[test]
+fn analyzer_answers_from_tablebase() {
+    let config = BoardConfig::new(3, 3, 3);
+    let tablebase = std::sync::Arc::new(Tablebase::generate(config).unwrap());
+    let mut analyzer = Analyzer::new().set_tablebase(tablebase);
+    let mut live = Analyzer::new();
+
+    let mut board = BitBoard::new(config);
+    board.set_side_to_move(1);
+    board.place(1, 1).unwrap();
+    assert_eq!(analyzer.analyze(&board), live.analyze(&board));
+}
+
+#[test]
+#[ignore = "slow: generates the 4x4 tablebase; run with --release -- --ignored"]
+fn four_by_four_tablebase_round_trips_through_a_file() {
+    let config = BoardConfig::new(4, 4, 4);
+    let tablebase = Tablebase::generate(config).unwrap();
+    let path = scratch_path(config);
+    tablebase.save(&path).unwrap();
+    let loaded = Tablebase::load(&path, config);
+    fs::remove_file(&path).unwrap();
+    let loaded = loaded.unwrap();
+
+    assert_eq!(loaded.config(), config);
+    assert_eq!(loaded.len(), tablebase.len());
+    let empty = BitBoard::new(config);
+    assert_eq!(loaded.get(&empty), Some(GameValue::Draw));
+    // every position two moves in, against the tablebase it was saved from
+    for first in empty.legal_moves() {
+        let mut board = empty.clone();
+        board.play(first);
+        assert_eq!(loaded.get(&board), tablebase.get(&board));
+        for second in board.legal_moves() {
+            let mut board = board.clone();
+            board.play(second);
+            assert!(loaded.get(&board).is_some());
+            assert_eq!(loaded.get(&board), tablebase.get(&board));
+        }
+    }
+}
+
+#[test]
+fn tablebase_for_another_board_is_refused() {
+    let config = BoardConfig::new(3, 3, 3);
+    let path = scratch_path(config);
+    Tablebase::generate(config).unwrap().save(&path).unwrap();
+    let results = [
+        BoardConfig::new(4, 3, 3),
+        BoardConfig::new(3, 4, 3),
+        BoardConfig::new(3, 3, 2),
+    ]
+    .map(|expected| (expected, Tablebase::load(&path, expected)));
+    let matching = Tablebase::load(&path, config);
+    fs::remove_file(&path).unwrap();
+
+    for (expected, result) in results {
+        assert!(
+            matches!(
+                result,
+                Err(TablebaseError::WrongBoard { expected: e, found }) if e == expected && found == config
+            ),
+            "{expected}: {:?}",
+            result.err()
+        );
+    }
+    assert_eq!(matching.unwrap().config(), config);
+}
@@ -0,0 +1,309 @@
+// pins the bytes of every message in both encodings; a change here is a
+// protocol change and needs PROTOCOL_VERSION bumped
+//
+// regenerate the files with UPDATE_GOLDEN=1 cargo test --test wire
+
+use std::{fs, path::PathBuf};
+
+use board_game::{
+    board::BoardConfig,
+    message::{
+        Envelope, ExecutorToManagerMsg, ManagerToExecutorMsg,
+        notification::ErrorNotification,
+        request_message::{ExecutorToManagerReqMsg, ManagerToExecutorReqMsg},
+        response_message::{ExecutorToManagerResMsg, ManagerToExecutorResMsg},
+        snapshot::{Outcome, StateSnapshot},
+    },
+    player::Player,
+    transport::{self, MAX_FRAME_LEN, TransportError},
+    wire::{self, Capability, Encoding, Hello, PROTOCOL_VERSION},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::{
+    io::AsyncWriteExt,
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+
+fn golden_path(name: &str, extension: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{name}.{extension}"))
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut text: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    text.push('\n');
+    text
+}
+
+fn check_golden(path: PathBuf, actual: String) {
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &actual).unwrap();
+        return;
+    }
+    let expected =
+        fs::read_to_string(&path).unwrap_or_else(|err| panic!("{}: {err}", path.display()));
+    assert_eq!(actual, expected, "{} changed", path.display());
+}
+
+// checks both encodings against their files and that each decodes back to
+// the same bytes
+fn check<T: Serialize + DeserializeOwned>(name: &str, message: &T) {
+    let json = wire::encode(Encoding::Json, message).unwrap();
+    let binary = wire::encode(Encoding::Binary, message).unwrap();
+    let mut json_text = String::from_utf8(json.clone()).unwrap();
+    json_text.push('\n');
+    check_golden(golden_path(name, "json"), json_text);
+    check_golden(golden_path(name, "hex"), hex(&binary));
+
+    let decoded: T = wire::decode(Encoding::Json, &json).unwrap();
+    assert_eq!(wire::encode(Encoding::Json, &decoded).unwrap(), json);
+    let decoded: T = wire::decode(Encoding::Binary, &binary).unwrap();
+    assert_eq!(wire::encode(Encoding::Binary, &decoded).unwrap(), binary);
+}
+
+// X has won along the top row of a 2x3 board
+fn snapshot() -> StateSnapshot {
+    let (x, o) = (Some(Player { id: 0 }), Some(Player { id: 1 }));
+    StateSnapshot {
+        config: BoardConfig::new(2, 3, 3),
+        board: vec![vec![x.clone(), x.clone(), x], vec![o.clone(), o, None]],
+        current_player: Player { id: 1 },
+        move_number: 5,
+        outcome: Outcome::Won(Player { id: 0 }, vec![(0, 0), (0, 1), (0, 2)]),
+    }
+}
+
+fn envelope<T>(body: T) -> Envelope<T> {
+    Envelope {
+        correlation_id: 7,
+        sequence: 300,
+        game_id: 2,
+        body,
+    }
+}
+
+#[test]
+fn hello() {
+    check(
+        "hello",
+        &Hello {
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: vec![Capability::Binary],
+        },
+    );
+}
+
+#[test]
+fn hello_with_unknown_capability() {
+    let hello: Hello = wire::decode(
+        Encoding::Json,
+        br#"{"protocol_version":1,"capabilities":["binary","compression"]}"#,
+    )
+    .unwrap();
+    assert_eq!(
+        hello.capabilities,
+        vec![Capability::Binary, Capability::Unknown]
+    );
+    assert_eq!(
+        Hello::new(Encoding::Binary).agreed_encoding(&hello),
+        Encoding::Binary
+    );
+    assert_eq!(
+        Hello::new(Encoding::Json).agreed_encoding(&hello),
+        Encoding::Json
+    );
+}
+
+#[test]
+fn manager_to_executor_requests() {
+    let requests = [
+        (
+            "init_game_request",
+            ManagerToExecutorReqMsg::InitGameRequest,
+        ),
+        (
+            "quit_game_request",
+            ManagerToExecutorReqMsg::QuitGameRequest,
+        ),
+        (
+            "execute_game_request",
+            ManagerToExecutorReqMsg::ExecuteGameRequest,
+        ),
+        (
+            "player_win_request",
+            ManagerToExecutorReqMsg::PlayerWinRequest(
+                Player { id: 1 },
+                vec![(0, 2), (1, 1), (2, 0)],
+            ),
+        ),
+        (
+            "sync_state_request",
+            ManagerToExecutorReqMsg::SyncStateRequest(snapshot()),
+        ),
+    ];
+    for (name, request) in requests {
+        check(name, &envelope(ManagerToExecutorMsg::Request(request)));
+    }
+}
+
+#[test]
+fn manager_to_executor_responses() {
+    let responses = [
+        (
+            "ready_to_quit_game_response",
+            ManagerToExecutorResMsg::ReadyToQuitGameResponse,
+        ),
+        (
+            "tile_on_by_player_response",
+            ManagerToExecutorResMsg::TileOnByPlayerResponse(true),
+        ),
+        (
+            "reset_game_response",
+            ManagerToExecutorResMsg::ResetGameResponse,
+        ),
+        (
+            "undo_move_response",
+            ManagerToExecutorResMsg::UndoMoveResponse,
+        ),
+        (
+            "state_snapshot_response",
+            ManagerToExecutorResMsg::StateSnapshotResponse(snapshot()),
+        ),
+    ];
+    for (name, response) in responses {
+        check(name, &envelope(ManagerToExecutorMsg::Response(response)));
+    }
+}
+
+#[test]
+fn error_notification() {
+    check(
+        "error_notification",
+        &envelope(ManagerToExecutorMsg::ErrorNotification(ErrorNotification {
+            message: "Cannot place a mark at (1, 1)".to_string(),
+            fatal: false,
+        })),
+    );
+}
+
+#[test]
+fn executor_to_manager_requests() {
+    let requests = [
+        (
+            "ready_to_quit_game_request",
+            ExecutorToManagerReqMsg::ReadyToQuitGameRequest,
+        ),
+        (
+            "tile_on_by_player_request",
+            ExecutorToManagerReqMsg::TileOnByPlayerRequesst(Player { id: 0 }, 1, 2),
+        ),
+        (
+            "reset_game_request",
+            ExecutorToManagerReqMsg::ResetGameRequest(BoardConfig::new(4, 5, 4), Player { id: 1 }),
+        ),
+        (
+            "undo_move_request",
+            ExecutorToManagerReqMsg::UndoMoveRequest(2, 1),
+        ),
+        (
+            "get_state_request",
+            ExecutorToManagerReqMsg::GetStateRequest,
+        ),
+    ];
+    for (name, request) in requests {
+        check(name, &envelope(ExecutorToManagerMsg::Request(request)));
+    }
+}
+
+#[test]
+fn executor_to_manager_responses() {
+    let responses = [
+        (
+            "init_game_response",
+            ExecutorToManagerResMsg::InitGameResponse,
+        ),
+        (
+            "quit_game_response",
+            ExecutorToManagerResMsg::QuitGameResponse,
+        ),
+        (
+            "execute_game_response",
+            ExecutorToManagerResMsg::ExecuteGameResponse,
+        ),
+        (
+            "player_win_response",
+            ExecutorToManagerResMsg::PlayerWinResponse,
+        ),
+    ];
+    for (name, response) in responses {
+        check(name, &envelope(ExecutorToManagerMsg::Response(response)));
+    }
+}
+
+// runs a bridge against a peer that sends `first_line` and returns how the
+// bridge ended
+async fn bridge_against(first_line: &str) -> Result<(), TransportError> {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let first_line = first_line.to_string();
+    let peer = tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        stream.write_all(first_line.as_bytes()).await.unwrap();
+        stream
+    });
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (tx, _rx) = mpsc::channel::<Envelope<ManagerToExecutorMsg>>(1);
+    let (_out_tx, out_rx) = mpsc::channel::<Envelope<ExecutorToManagerMsg>>(1);
+    let result = transport::bridge(stream, Encoding::Binary, tx, out_rx).await;
+    drop(peer.await.unwrap());
+    result
+}
+
+#[tokio::test]
+async fn version_mismatch_is_refused() {
+    let result = bridge_against("{\"protocol_version\":999,\"capabilities\":[]}\n").await;
+    let Err(err @ TransportError::VersionMismatch { ours, theirs: 999 }) = result else {
+        panic!("expected a version mismatch, got {result:?}");
+    };
+    assert_eq!(ours, PROTOCOL_VERSION);
+    assert!(err.to_string().contains("version 999"));
+}
+
+#[tokio::test]
+async fn missing_hello_is_refused() {
+    let line = fs::read_to_string(golden_path("init_game_request", "json")).unwrap();
+    let result = bridge_against(&line).await;
+    assert!(matches!(result, Err(TransportError::NoHello)), "{result:?}");
+}
+
+#[tokio::test]
+async fn an_oversized_binary_frame_is_refused() {
+    let header = (MAX_FRAME_LEN as u32 + 1).to_be_bytes();
+    let result = transport::read_frame(&mut &header[..], Encoding::Binary).await;
+    assert!(
+        matches!(result, Err(TransportError::FrameTooLarge(len)) if len == MAX_FRAME_LEN + 1),
+        "{result:?}"
+    );
+}
+
+#[tokio::test]
+async fn an_oversized_json_frame_is_refused() {
+    // no newline comes within the limit, so the rest is never buffered
+    let line = vec![b' '; 2 * MAX_FRAME_LEN];
+    let result = transport::read_frame(&mut &line[..], Encoding::Json).await;
+    assert!(
+        matches!(result, Err(TransportError::FrameTooLarge(len)) if len == MAX_FRAME_LEN + 1),
+        "{result:?}"
+    );
+
+    let mut line = vec![b' '; MAX_FRAME_LEN];
+    line.push(b'\n');
+    let frame = transport::read_frame(&mut &line[..], Encoding::Json).await;
+    assert_eq!(
+        frame.unwrap().map(|frame| frame.len()),
+        Some(MAX_FRAME_LEN + 1)
+    );
+}
@@ -0,0 +1,192 @@
+use std::{env, fs, path::PathBuf};
+
+use board_game::{
+    board::BoardConfig,
+    consts::{MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{keymap::Keymap, theme::ThemeSettings};
+
+pub const LOG_LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    #[error("Io error: {0:?}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid config file: {0}")]
+    ParseError(#[from] toml::de::Error),
+    #[error("Failed to write config: {0}")]
+    SerializeError(#[from] toml::ser::Error),
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFormat {
+    #[default]
+    Text,
+    // one JSON object per line, with the fields of every span it is in
+    Json,
+}
+
+impl LogFormat {
+    pub fn label(self) -> &'static str {
+        match self {
+            LogFormat::Text => "text",
+            LogFormat::Json => "json",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub board: BoardConfig,
+    pub log_level: String,
+    pub log_format: LogFormat,
+    // daily log files kept; older ones are deleted on the next rotation
+    pub log_files: usize,
+    pub animations: bool,
+    pub theme: ThemeSettings,
+    pub keymap: Keymap,
+    // where solved tablebases are looked up, next to the executable if unset
+    pub tablebase_dir: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| ".".into())
+            .join("tictactoe")
+            .join("config.toml")
+    }
+
+    // a missing file gives the defaults
+    pub fn load(path: &PathBuf) -> Result<Self, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str::<Config>(&content)?.validated()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    pub fn tablebase_dir(&self) -> PathBuf {
+        self.tablebase_dir.clone().unwrap_or_else(|| {
+            env::current_exe()
+                .ok()
+                .and_then(|exe| Some(exe.parent()?.join("tablebases")))
+                .unwrap_or_else(|| "tablebases".into())
+        })
+    }
+
+    pub fn save(&self, path: &PathBuf) -> Result<(), ConfigError> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // clamps values a hand-edited file may have put out of range
+    pub fn validated(mut self) -> Self {
+        let board = &mut self.board;
+        board.rows = board.rows.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE);
+        board.cols = board.cols.clamp(MIN_BOARD_SIZE, MAX_BOARD_SIZE);
+        board.k = board.k.clamp(MIN_BOARD_SIZE, board.rows.max(board.cols));
+        if !LOG_LEVELS.contains(&self.log_level.as_str()) {
+            self.log_level = Config::default().log_level;
+        }
+        self.log_files = self.log_files.max(1);
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            board: BoardConfig::default(),
+            log_level: "info".to_string(),
+            log_format: LogFormat::Text,
+            log_files: 7,
+            animations: true,
+            theme: ThemeSettings::default(),
+            keymap: Keymap::default(),
+            tablebase_dir: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+    use crate::keymap::{Action, KeymapPreset};
+
+    // a path of its own for each test, in a directory that doesn't exist yet
+    fn scratch(name: &str) -> PathBuf {
+        env::temp_dir()
+            .join(format!("{}-config-{name}", process::id()))
+            .join("config.toml")
+    }
+
+    #[test]
+    fn a_saved_config_loads_back() {
+        let path = scratch("round-trip");
+        let mut config = Config {
+            board: BoardConfig::new(4, 5, 4),
+            log_level: "debug".to_string(),
+            log_format: LogFormat::Json,
+            animations: false,
+            tablebase_dir: Some("/opt/tictactoe/tablebases".into()),
+            ..Config::default()
+        };
+        config.keymap.preset = KeymapPreset::Vim;
+        config.keymap.bind(Action::Undo, "x".to_string());
+        config.save(&path).unwrap();
+
+        let loaded = Config::load(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert_eq!(loaded.unwrap(), config);
+    }
+
+    #[test]
+    fn a_missing_file_gives_the_defaults() {
+        let loaded = Config::load(&scratch("missing")).unwrap();
+        assert_eq!(loaded, Config::default());
+    }
+
+    #[test]
+    fn an_invalid_file_is_an_error() {
+        let path = scratch("invalid");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "log_level = [\"not\", \"a string\"]\n").unwrap();
+        let loaded = Config::load(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+        assert!(matches!(loaded, Err(ConfigError::ParseError(_))));
+    }
+
+    #[test]
+    fn a_partial_file_is_completed_and_clamped() {
+        let path = scratch("partial");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        let content =
+            "log_level = \"loud\"\nlog_files = 0\n\n[board]\nrows = 20\ncols = 1\nk = 30\n";
+        fs::write(&path, content).unwrap();
+        let loaded = Config::load(&path);
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(
+            loaded.board,
+            BoardConfig::new(MAX_BOARD_SIZE, MIN_BOARD_SIZE, MAX_BOARD_SIZE)
+        );
+        assert_eq!(loaded.log_level, "info");
+        assert_eq!(loaded.log_files, 1);
+        assert_eq!(loaded.keymap, Keymap::default());
+        assert!(loaded.animations);
+        let beside_the_tests = env::current_exe().unwrap().with_file_name("tablebases");
+        assert_eq!(loaded.tablebase_dir(), beside_the_tests);
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use crossterm::event::KeyCode;
+use num_enum::TryFromPrimitive;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TryFromPrimitive,
+)]
+#[serde(rename_all = "snake_case")]
+#[repr(usize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Place,
+    Undo,
+    Hint,
+    Back,
+    Quit,
+    Help,
+    // only on the screens that offer them
+    Reset,
+    Confirm,
+    First,
+    Last,
+}
+
+pub const ACTION_NUMS: usize = 14;
+
+impl Action {
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move up",
+            Action::MoveDown => "Move down",
+            Action::MoveLeft => "Move left",
+            Action::MoveRight => "Move right",
+            Action::Place => "Place / select",
+            Action::Undo => "Undo",
+            Action::Hint => "Hint",
+            Action::Back => "Back",
+            Action::Quit => "Quit",
+            Action::Help => "Help",
+            Action::Reset => "Reset statistics",
+            Action::Confirm => "Confirm",
+            Action::First => "Jump to start",
+            Action::Last => "Jump to end",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TryFromPrimitive)]
+#[serde(rename_all = "snake_case")]
+#[repr(usize)]
+pub enum KeymapPreset {
+    #[default]
+    Arrows,
+    Vim,
+    Wasd,
+}
+
+pub const KEYMAP_PRESET_NUMS: usize = 3;
+
+impl KeymapPreset {
+    pub fn label(self) -> &'static str {
+        match self {
+            KeymapPreset::Arrows => "arrows",
+            KeymapPreset::Vim => "vim",
+            KeymapPreset::Wasd => "wasd",
+        }
+    }
+
+    fn keys(self, action: Action) -> &'static [&'static str] {
+        match (self, action) {
+            (KeymapPreset::Arrows, Action::MoveUp) => &["Up"],
+            (KeymapPreset::Arrows, Action::MoveDown) => &["Down", "Tab"],
+            (KeymapPreset::Arrows, Action::MoveLeft) => &["Left"],
+            (KeymapPreset::Arrows, Action::MoveRight) => &["Right"],
+            (KeymapPreset::Vim, Action::MoveUp) => &["k", "Up"],
+            (KeymapPreset::Vim, Action::MoveDown) => &["j", "Down"],
+            (KeymapPreset::Vim, Action::MoveLeft) => &["h", "Left"],
+            (KeymapPreset::Vim, Action::MoveRight) => &["l", "Right"],
+            (KeymapPreset::Wasd, Action::MoveUp) => &["w", "Up"],
+            (KeymapPreset::Wasd, Action::MoveDown) => &["s", "Down"],
+            (KeymapPreset::Wasd, Action::MoveLeft) => &["a", "Left"],
+            (KeymapPreset::Wasd, Action::MoveRight) => &["d", "Right"],
+            (KeymapPreset::Wasd, Action::Place) => &["Space", "Enter"],
+            (_, Action::Place) => &["Enter", "Space"],
+            (_, Action::Undo) => &["u"],
+            (_, Action::Hint) => &["?"],
+            (_, Action::Back) => &["Esc"],
+            (_, Action::Quit) => &["q"],
+            (_, Action::Help) => &["F1"],
+            (_, Action::Reset) => &["r"],
+            (_, Action::Confirm) => &["y"],
+            (_, Action::First) => &["Home"],
+            (_, Action::Last) => &["End"],
+        }
+    }
+}
+
+// maps keys to actions; the screens only ever see actions, apart from text
+// entry in the settings
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Keymap {
+    pub preset: KeymapPreset,
+    // keys listed for an action replace the preset's keys for it
+    pub bindings: BTreeMap<Action, Vec<String>>,
+}
+
+impl Keymap {
+    pub fn keys(&self, action: Action) -> Vec<String> {
+        match self.bindings.get(&action) {
+            Some(keys) => keys.clone(),
+            None => self
+                .preset
+                .keys(action)
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+        }
+    }
+
+    // the first key of an action, for short hints
+    pub fn key(&self, action: Action) -> String {
+        self.keys(action).into_iter().next().unwrap_or_default()
+    }
+
+    // when a key is bound twice the earlier action wins
+    pub fn action(&self, key_code: KeyCode) -> Option<Action> {
+        (0..ACTION_NUMS)
+            .map(|index| Action::try_from(index).unwrap())
+            .find(|&action| {
+                self.keys(action)
+                    .iter()
+                    .any(|key| parse_key(key) == Some(key_code))
+            })
+    }
+
+    pub fn bind(&mut self, action: Action, key_name: String) {
+        self.bindings.insert(action, vec![key_name]);
+    }
+
+    // other actions that share a key with `action`
+    pub fn conflicts(&self, action: Action) -> Vec<Action> {
+        let key_codes: Vec<_> = self
+            .keys(action)
+            .iter()
+            .filter_map(|key| parse_key(key))
+            .collect();
+        (0..ACTION_NUMS)
+            .map(|index| Action::try_from(index).unwrap())
+            .filter(|&other| {
+                other != action
+                    && self
+                        .keys(other)
+                        .iter()
+                        .any(|key| parse_key(key).is_some_and(|code| key_codes.contains(&code)))
+            })
+            .collect()
+    }
+}
+
+pub fn parse_key(name: &str) -> Option<KeyCode> {
+    let mut chars = name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Some(KeyCode::Char(c));
+    }
+    Some(match name.to_ascii_lowercase().as_str() {
+        "up" => KeyCode::Up,
+        "down" => KeyCode::Down,
+        "left" => KeyCode::Left,
+        "right" => KeyCode::Right,
+        "enter" => KeyCode::Enter,
+        "esc" => KeyCode::Esc,
+        "tab" => KeyCode::Tab,
+        "space" => KeyCode::Char(' '),
+        "backspace" => KeyCode::Backspace,
+        "home" => KeyCode::Home,
+        "end" => KeyCode::End,
+        "pageup" => KeyCode::PageUp,
+        "pagedown" => KeyCode::PageDown,
+        "delete" => KeyCode::Delete,
+        "insert" => KeyCode::Insert,
+        name => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+    })
+}
+
+pub fn key_name(key_code: KeyCode) -> Option<String> {
+    Some(match key_code {
+        KeyCode::Char(' ') => "Space".to_string(),
+        KeyCode::Char(c) => c.to_string(),
+        KeyCode::Up => "Up".to_string(),
+        KeyCode::Down => "Down".to_string(),
+        KeyCode::Left => "Left".to_string(),
+        KeyCode::Right => "Right".to_string(),
+        KeyCode::Enter => "Enter".to_string(),
+        KeyCode::Esc => "Esc".to_string(),
+        KeyCode::Tab => "Tab".to_string(),
+        KeyCode::Backspace => "Backspace".to_string(),
+        KeyCode::Home => "Home".to_string(),
+        KeyCode::End => "End".to_string(),
+        KeyCode::PageUp => "PageUp".to_string(),
+        KeyCode::PageDown => "PageDown".to_string(),
+        KeyCode::Delete => "Delete".to_string(),
+        KeyCode::Insert => "Insert".to_string(),
+        KeyCode::F(n) => format!("F{n}"),
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_names_parse_in_any_case() {
+        assert_eq!(parse_key("x"), Some(KeyCode::Char('x')));
+        assert_eq!(parse_key("?"), Some(KeyCode::Char('?')));
+        assert_eq!(parse_key("Space"), Some(KeyCode::Char(' ')));
+        assert_eq!(parse_key("ENTER"), Some(KeyCode::Enter));
+        assert_eq!(parse_key("pageDown"), Some(KeyCode::PageDown));
+        assert_eq!(parse_key("F12"), Some(KeyCode::F(12)));
+        assert_eq!(parse_key("f1"), Some(KeyCode::F(1)));
+        for name in ["", "Fx", "Ctrl", "spacebar"] {
+            assert_eq!(parse_key(name), None, "{name:?} parsed");
+        }
+    }
+
+    #[test]
+    fn key_names_survive_the_round_trip() {
+        for key_code in [
+            KeyCode::Char('k'),
+            KeyCode::Char(' '),
+            KeyCode::Up,
+            KeyCode::Esc,
+            KeyCode::Home,
+            KeyCode::End,
+            KeyCode::F(5),
+        ] {
+            let name = key_name(key_code).unwrap();
+            assert_eq!(parse_key(&name), Some(key_code), "{name}");
+        }
+        assert_eq!(key_name(KeyCode::Null), None);
+    }
+
+    #[test]
+    fn every_preset_binds_every_action() {
+        for preset in 0..KEYMAP_PRESET_NUMS {
+            let keymap = Keymap {
+                preset: KeymapPreset::try_from(preset).unwrap(),
+                ..Keymap::default()
+            };
+            for index in 0..ACTION_NUMS {
+                let action = Action::try_from(index).unwrap();
+                let keys = keymap.keys(action);
+                assert!(!keys.is_empty(), "{action:?} has no key");
+                for key in &keys {
+                    assert!(parse_key(key).is_some(), "{key} does not parse");
+                }
+                assert!(keymap.conflicts(action).is_empty(), "{action:?} conflicts");
+            }
+        }
+    }
+
+    #[test]
+    fn bindings_replace_the_preset_keys() {
+        let keymap: Keymap = toml::from_str(
+            "preset = \"vim\"\n[bindings]\nundo = [\"x\"]\nhint = [\"?\", \"Space\"]\n",
+        )
+        .unwrap();
+        assert_eq!(keymap.preset, KeymapPreset::Vim);
+        assert_eq!(keymap.action(KeyCode::Char('x')), Some(Action::Undo));
+        assert_eq!(keymap.action(KeyCode::Char('u')), None);
+        assert_eq!(keymap.action(KeyCode::Char('h')), Some(Action::MoveLeft));
+        assert_eq!(keymap.key(Action::Hint), "?");
+        // Space stays with place, which comes first
+        assert_eq!(keymap.action(KeyCode::Char(' ')), Some(Action::Place));
+        assert_eq!(keymap.conflicts(Action::Hint), [Action::Place]);
+    }
+}
@@ -1,286 +1,1786 @@
 use std::{
-    fmt::Debug,
-    str::{FromStr, SplitWhitespace},
+    mem::{self, Discriminant},
+    path::PathBuf,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
-use anyhow::Context;
 use async_trait::async_trait;
 use board_game::{
-    game_executor::{GameExecutor, GameExecutorError},
+    ai::Ai,
+    analysis::{AnalysisError, Analyzer, MoveAnalysis, MoveReview},
+    bitboard::BitBoard,
+    board::BoardConfig,
+    consts::PLAYER_NUM,
+    game_executor::{ExecutorOutbox, GameExecutor, GameExecutorError},
     message::{
-        ExecutorToManagerMsg, ManagerToExecutorMsg, request_message::ExecutorToManagerReqMsg,
+        Envelope, ExecutorToManagerMsg, ManagerToExecutorMsg, notification::ErrorNotification,
+        request_message::ExecutorToManagerReqMsg, response_message::ManagerToExecutorResMsg,
+        snapshot::StateSnapshot,
     },
+    outbox::Outbox,
     player::Player,
+    record::GameRecord,
+    stats::{Statistics, StatsStore, Streak},
 };
 
-use color_eyre::owo_colors::OwoColorize;
+use config::Config;
 use crossterm::event::EventStream;
-use futures::{FutureExt, StreamExt};
+use futures::StreamExt;
+use keymap::{ACTION_NUMS, Action, Keymap, key_name};
 use ratatui::{
     Frame, Terminal,
-    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind},
-    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    crossterm::event::{
+        Event, KeyCode, KeyEventKind, KeyModifiers, MouseButton, MouseEvent, MouseEventKind,
+    },
+    layout::{Constraint, Direction, Layout, Rect},
     prelude::Backend,
-    style::{Color, Modifier, Style},
-    text::{Span, Text},
-    widgets::{Block, Borders, Paragraph},
-};
-use tokio::{
-    sync::{Mutex, Notify, mpsc},
-    task::spawn_blocking,
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
-use tracing::{debug, info, trace};
-use tracing_subscriber::field::debug;
+use theme::Theme;
+use tokio::sync::{Mutex, mpsc};
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, info, trace, warn};
 use ui::{
+    animation::{self, FADE_DURATION, FLASH_DURATION, FRAME_INTERVAL, MARK_DURATION},
+    board_style::BoardStyle,
     screen::{
         game_on_screen::GameOnScreen,
-        main_screen::{self, CurrentSelectMenu, MainScreen, SELECT_MENU_NUMS},
+        game_over_screen::{GAME_OVER_MENU_NUMS, GameOverMenu, GameOverScreen},
+        main_screen::{CurrentSelectMenu, MainScreen, SELECT_MENU_NUMS},
+        replay_screen::ReplayScreen,
+        review_screen::ReviewScreen,
+        settings_screen::{SETTINGS_ITEM_NUMS, SettingsItem, SettingsKind, SettingsScreen},
+        statistics_screen::StatisticsScreen,
     },
     state::CurrentScreen,
 };
 
+pub mod config;
+
+pub mod keymap;
+
+pub mod logging;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+pub mod theme;
+
 mod ui;
 
+pub use ui::screen::game_on_screen::MARKS;
+
+// menus and banners are this wide when there is room, and need at least
+// the minimum
+const MENU_WIDTH: u16 = 48;
+const MENU_WIDTH_MIN: u16 = 24;
+
+// the AI always plays O
+const AI_PLAYER: usize = 1;
+
+// the screen shown once the manager has set up the game
+pub enum Launch {
+    Menu,
+    // a new game on the configured board
+    Play,
+    Replay(GameRecord),
+}
+
+enum ManagerEvent {
+    // whether the move just sent in the game with this ID won it
+    MoveResult(u64, bool),
+    // the move just sent in the game with this ID did not go through
+    MoveFailed(u64, GameExecutorError),
+    // the manager's board for the game with this ID
+    Snapshot(u64, StateSnapshot),
+    PlayerWon(Player, Vec<(usize, usize)>),
+    // an error the game goes on after
+    Error(GameExecutorError),
+    // the move the AI chose in the game with this ID, for the board it searched
+    AiMove(u64, BitBoard, Option<usize>),
+    // the best moves on a board of the game with this ID, None when the board
+    // is too large to search
+    Hint(u64, BitBoard, Option<Vec<MoveAnalysis>>),
+    // the review of these moves of the game with this ID, None when the board
+    // is too large to search
+    Review(
+        u64,
+        Vec<usize>,
+        Option<Result<Vec<MoveReview>, AnalysisError>>,
+    ),
+}
+
 pub struct TuiGameExecutor<B: Backend + std::marker::Send + std::marker::Sync> {
-    tx: Option<Arc<Mutex<mpsc::Sender<ExecutorToManagerMsg>>>>,
-    rx: Option<Arc<Mutex<mpsc::Receiver<ManagerToExecutorMsg>>>>,
-    is_win: Arc<Mutex<bool>>,
-    is_win_notify: Arc<Notify>,
-    is_tile_on_notify: Arc<Notify>,
+    tx: Option<Arc<ExecutorOutbox>>,
+    rx: Option<Arc<Mutex<mpsc::Receiver<Envelope<ManagerToExecutorMsg>>>>>,
+    // manager messages and search results handed to the UI loop
+    manager_events_tx: mpsc::Sender<ManagerEvent>,
+    manager_events_rx: Arc<Mutex<mpsc::Receiver<ManagerEvent>>>,
     terminal: Arc<Mutex<Terminal<B>>>,
     current_screen: Arc<Mutex<CurrentScreen>>,
     quit_game_now: Arc<Mutex<bool>>,
     event_strem: Arc<Mutex<EventStream>>,
+    analyzer: Arc<Mutex<Analyzer>>,
+    stats_store: Option<Arc<StatsStore>>,
+    config: Arc<Mutex<Config>>,
+    config_path: Option<PathBuf>,
+    launch: Launch,
+    ai: Option<Arc<Mutex<Ai>>>,
+    record_path: Option<PathBuf>,
+    show_help: Arc<Mutex<bool>>,
+    // shown over the screen until the next key press
+    error_message: Arc<Mutex<Option<String>>>,
+    // the kind of screen last drawn and since when, for the fade between screens
+    screen_shown: Arc<Mutex<(Discriminant<CurrentScreen>, Instant)>>,
+    cancel_token: CancellationToken,
 }
 
 impl<B: Backend + std::marker::Send + std::marker::Sync> TuiGameExecutor<B> {
     pub fn new(terminal: Arc<Mutex<Terminal<B>>>) -> Self {
+        let (manager_events_tx, manager_events_rx) = mpsc::channel(32);
         Self {
             tx: None,
             rx: None,
-            is_win: Arc::new(Mutex::new(false)),
-            is_win_notify: Arc::new(Notify::new()),
-            is_tile_on_notify: Arc::new(Notify::new()),
+            manager_events_tx,
+            manager_events_rx: Arc::new(Mutex::new(manager_events_rx)),
             terminal,
             current_screen: Arc::new(Mutex::new(CurrentScreen::Main(MainScreen::new()))),
             quit_game_now: Arc::new(Mutex::new(false)),
             event_strem: Arc::new(Mutex::new(EventStream::new())),
+            analyzer: Arc::new(Mutex::new(Analyzer::new())),
+            stats_store: None,
+            config: Arc::new(Mutex::new(Config::default())),
+            config_path: None,
+            launch: Launch::Menu,
+            ai: None,
+            record_path: None,
+            show_help: Arc::new(Mutex::new(false)),
+            error_message: Arc::new(Mutex::new(None)),
+            screen_shown: Arc::new(Mutex::new((
+                mem::discriminant(&CurrentScreen::Main(MainScreen::new())),
+                Instant::now(),
+            ))),
+            cancel_token: CancellationToken::new(),
         }
     }
 
-    pub fn set_rx(mut self, rx: mpsc::Receiver<ManagerToExecutorMsg>) -> Self {
+    pub fn set_rx(mut self, rx: mpsc::Receiver<Envelope<ManagerToExecutorMsg>>) -> Self {
         self.rx = Some(Arc::new(Mutex::new(rx)));
         self
     }
 
-    pub fn set_tx(mut self, tx: mpsc::Sender<ExecutorToManagerMsg>) -> Self {
-        self.tx = Some(Arc::new(Mutex::new(tx)));
+    pub fn set_tx(mut self, tx: mpsc::Sender<Envelope<ExecutorToManagerMsg>>) -> Self {
+        self.tx = Some(Arc::new(Outbox::new(tx)));
+        self
+    }
+
+    pub fn set_cancel_token(mut self, cancel_token: CancellationToken) -> Self {
+        self.cancel_token = cancel_token;
+        self
+    }
+
+    pub fn set_analyzer(mut self, analyzer: Analyzer) -> Self {
+        self.analyzer = Arc::new(Mutex::new(analyzer));
+        self
+    }
+
+    pub fn set_stats_store(mut self, stats_store: StatsStore) -> Self {
+        self.stats_store = Some(Arc::new(stats_store));
+        self
+    }
+
+    pub fn set_config(mut self, config: Config) -> Self {
+        self.config = Arc::new(Mutex::new(config));
+        self
+    }
+
+    // where the settings screen saves the config
+    pub fn set_config_path(mut self, config_path: PathBuf) -> Self {
+        self.config_path = Some(config_path);
+        self
+    }
+
+    pub fn set_launch(mut self, launch: Launch) -> Self {
+        self.launch = launch;
+        self
+    }
+
+    pub fn set_ai(mut self, ai: Ai) -> Self {
+        self.ai = Some(Arc::new(Mutex::new(ai)));
+        self
+    }
+
+    // every finished game overwrites this file
+    pub fn set_record_path(mut self, record_path: PathBuf) -> Self {
+        self.record_path = Some(record_path);
         self
     }
 
-    pub fn ui(current_screen: &mut CurrentScreen, frame: &mut Frame<'_>) {
-        if let CurrentScreen::Main(main_screen) = current_screen {
-            let title_block = Block::default()
-                .borders(Borders::ALL)
-                .style(Style::default());
+    pub fn ui(
+        current_screen: &mut CurrentScreen,
+        config: &Config,
+        show_help: bool,
+        error_message: Option<&str>,
+        shown_at: Instant,
+        frame: &mut Frame<'_>,
+    ) {
+        // the settings screen previews the theme being edited
+        let theme = &match current_screen {
+            CurrentScreen::Settings(settings_screen) => settings_screen.config.theme.theme(),
+            _ => config.theme.theme(),
+        };
+        frame.render_widget(
+            Block::default().style(Style::default().bg(theme.background).fg(theme.text)),
+            frame.area(),
+        );
+        let (min_width, min_height) = Self::min_size(current_screen);
+        let area = frame.area();
+        if area.width < min_width || area.height < min_height {
+            Self::too_small_ui((min_width, min_height), theme, frame);
+            return;
+        }
+        match current_screen {
+            CurrentScreen::Main(main_screen) => Self::main_ui(main_screen, theme, frame),
+            CurrentScreen::GameOn(game_on_screen) => {
+                Self::game_on_ui(game_on_screen, config, theme, frame)
+            }
+            CurrentScreen::GameOver(game_over_screen) => {
+                Self::game_over_ui(game_over_screen, config.animations, theme, frame)
+            }
+            CurrentScreen::Review(review_screen) => {
+                Self::review_ui(review_screen, &config.keymap, theme, frame)
+            }
+            CurrentScreen::Statistics(statistics_screen) => {
+                Self::statistics_ui(statistics_screen, &config.keymap, theme, frame)
+            }
+            CurrentScreen::Settings(settings_screen) => {
+                Self::settings_ui(settings_screen, theme, frame)
+            }
+            CurrentScreen::Replay(replay_screen) => {
+                Self::replay_ui(replay_screen, &config.keymap, theme, frame)
+            }
+        }
+        if show_help {
+            Self::help_ui(&config.keymap, theme, frame);
+        }
+        if let Some(error_message) = error_message {
+            Self::error_ui(error_message, theme, frame);
+        }
+        if let Some(progress) = animation::progress(shown_at, FADE_DURATION) {
+            if config.animations {
+                animation::fade(frame.buffer_mut(), theme.background, progress);
+            }
+        }
+    }
+
+    // how long until the screen changes on its own, None when only input or
+    // the manager can change it
+    fn redraw_after(
+        current_screen: &CurrentScreen,
+        config: &Config,
+        shown_at: Instant,
+    ) -> Option<Duration> {
+        let animating = shown_at.elapsed() < FADE_DURATION
+            || match current_screen {
+                CurrentScreen::GameOn(game_on_screen) => game_on_screen
+                    .placed_at
+                    .last()
+                    .is_some_and(|placed_at| placed_at.elapsed() < MARK_DURATION),
+                CurrentScreen::GameOver(game_over_screen) => {
+                    game_over_screen.finished_at.elapsed() < FLASH_DURATION
+                }
+                _ => false,
+            };
+        if config.animations && animating {
+            return Some(FRAME_INTERVAL);
+        }
+        match current_screen {
+            // the clock ticks over on the next whole second
+            CurrentScreen::GameOn(game_on_screen) => {
+                let subsec = game_on_screen.started_at.elapsed().subsec_nanos();
+                Some(Duration::from_nanos(1_000_000_000 - subsec as u64))
+            }
+            _ => None,
+        }
+    }
+
+    // the smallest terminal each screen can be drawn in without overlapping
+    fn min_size(current_screen: &CurrentScreen) -> (u16, u16) {
+        let compact_board = |config: BoardConfig| BoardStyle::Compact.size(config);
+        match current_screen {
+            CurrentScreen::Main(_) => (MENU_WIDTH_MIN, 3 + 3 * SELECT_MENU_NUMS as u16),
+            CurrentScreen::GameOn(game_on_screen) => {
+                let (width, height) = compact_board(game_on_screen.board.config());
+                (width.max(MENU_WIDTH_MIN), height + 3)
+            }
+            CurrentScreen::GameOver(game_over_screen) => {
+                let (width, height) = compact_board(game_over_screen.board.config());
+                (
+                    width.max(MENU_WIDTH_MIN),
+                    3 + height + 2 + 3 * GAME_OVER_MENU_NUMS as u16,
+                )
+            }
+            // the move list and the bordered board side by side
+            CurrentScreen::Review(ReviewScreen { config, .. })
+            | CurrentScreen::Replay(ReplayScreen {
+                record: GameRecord { config, .. },
+                ..
+            }) => {
+                // both halves share 80% of the screen
+                let (width, height) = compact_board(*config);
+                (
+                    (width + 2).max(MENU_WIDTH_MIN) * 5 / 2,
+                    (height + 2) * 5 / 4 + 1,
+                )
+            }
+            CurrentScreen::Statistics(_) | CurrentScreen::Settings(_) => (40, 12),
+        }
+    }
+
+    fn too_small_ui(min_size: (u16, u16), theme: &Theme, frame: &mut Frame<'_>) {
+        let area = frame.area();
+        let lines = vec![
+            Line::styled(
+                "Terminal too small",
+                Style::default()
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Line::from(format!("need {}x{}", min_size.0, min_size.1)),
+            Line::from(format!("have {}x{}", area.width, area.height)),
+        ];
+        let [_, middle, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(lines.len() as u16),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        frame.render_widget(Paragraph::new(lines).centered(), middle);
+    }
+
+    fn main_ui(main_screen: &mut MainScreen, theme: &Theme, frame: &mut Frame<'_>) {
+        let title_block = Self::bordered(theme);
+
+        let title_text = Text::from(Span::styled(
+            "Tic-Tac-Toe",
+            Style::default()
+                .add_modifier(Modifier::BOLD)
+                .fg(theme.title),
+        ));
+
+        // the title shrinks to a single line in its box on short terminals
+        let area = Self::centered_width(60, frame.area());
+        let title_height = if area.height >= 24 { 7 } else { 3 };
+        let [_, title_area, _, menu_area, _] = Layout::vertical([
+            Constraint::Fill(1),
+            Constraint::Length(title_height),
+            Constraint::Fill(1),
+            Constraint::Length(3 * SELECT_MENU_NUMS as u16),
+            Constraint::Fill(1),
+        ])
+        .areas(area);
+        let title_paragraph_block = Paragraph::new("").block(title_block).centered();
+        let title_paragraph_text = Paragraph::new(title_text).centered();
+        frame.render_widget(title_paragraph_block, title_area);
+        frame.render_widget(
+            title_paragraph_text,
+            Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Fill(1),
+                    Constraint::Length(1),
+                    Constraint::Fill(1),
+                ])
+                .split(title_area)[1],
+        );
+
+        let menu_chuncks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3); SELECT_MENU_NUMS])
+            .split(menu_area);
+
+        main_screen.menu_areas = menu_chuncks.to_vec();
+        for (index, area) in menu_chuncks.iter().enumerate() {
+            let menu = CurrentSelectMenu::try_from(index).unwrap();
+            let mut block = Self::bordered(theme);
+            let mut text = Text::styled(menu.label(), Style::default());
+            if main_screen.menu_select.map(|select| select as usize) == Some(index) {
+                text = text.style(
+                    Style::default()
+                        .fg(theme.selection)
+                        .add_modifier(Modifier::BOLD),
+                );
+                block = block.style(Style::default().bg(theme.highlight));
+            }
+            frame.render_widget(Paragraph::new(text).block(block).centered(), *area);
+        }
+    }
+
+    fn game_on_ui(
+        game_on_screen: &mut GameOnScreen,
+        config: &Config,
+        theme: &Theme,
+        frame: &mut Frame<'_>,
+    ) {
+        let keymap = &config.keymap;
+        let board = &game_on_screen.board;
+        let appearing: Vec<_> = if config.animations {
+            game_on_screen
+                .moves
+                .iter()
+                .zip(&game_on_screen.placed_at)
+                .filter_map(|(&index, &placed_at)| {
+                    animation::progress(placed_at, MARK_DURATION)
+                        .map(|progress| (board.position(index), progress))
+                })
+                .collect()
+        } else {
+            vec![]
+        };
+        let config = game_on_screen.board.config();
+        let area = frame.area();
+        let board_style = BoardStyle::fitting(
+            config,
+            Rect::new(area.x, area.y, area.width, area.height.saturating_sub(3)),
+        )
+        .unwrap_or(BoardStyle::Compact);
+        let board_lines = Self::board_lines(
+            &game_on_screen.board,
+            theme,
+            board_style,
+            Some(game_on_screen.cursor),
+            &game_on_screen.hint,
+            &game_on_screen.winning_line,
+            &appearing,
+        );
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(board_style.size(config).1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Length(1),
+                Constraint::Fill(1),
+            ])
+            .split(frame.area());
+        game_on_screen.board_area = chunks[1];
+        game_on_screen.board_style = board_style;
+        frame.render_widget(Paragraph::new(board_lines).centered(), chunks[1]);
+
+        let elapsed = game_on_screen.started_at.elapsed().as_secs();
+        let mut status = format!(
+            "{} to move  {}:{:02}",
+            MARKS[game_on_screen.board.side_to_move()],
+            elapsed / 60,
+            elapsed % 60
+        );
+        if game_on_screen.ai_thinking {
+            status.push_str("  (AI thinking...)");
+        } else if game_on_screen.hint_pending {
+            status.push_str("  (looking for a hint...)");
+        } else if let Some(value) = game_on_screen.hint_value {
+            status.push_str(&format!("  (best move: {value})"));
+        } else if game_on_screen.hint_unavailable {
+            status.push_str("  (hint unavailable for this board)");
+        }
+        let help = format!(
+            "{}: place  {}: undo  {}: hint  {}: menu  {}: all keys",
+            keymap.key(Action::Place),
+            keymap.key(Action::Undo),
+            keymap.key(Action::Hint),
+            keymap.key(Action::Back),
+            keymap.key(Action::Help)
+        );
+        frame.render_widget(
+            Paragraph::new(Text::styled(
+                status,
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+            .centered(),
+            chunks[3],
+        );
+        frame.render_widget(
+            Paragraph::new(Text::styled(help, Style::default().fg(theme.help))).centered(),
+            chunks[4],
+        );
+    }
+
+    fn game_over_ui(
+        game_over_screen: &mut GameOverScreen,
+        animate: bool,
+        theme: &Theme,
+        frame: &mut Frame<'_>,
+    ) {
+        let config = game_over_screen.board.config();
+        let area = frame.area();
+        let menu_height = 3 * GAME_OVER_MENU_NUMS as u16;
+        let board_style = BoardStyle::fitting(
+            config,
+            Rect::new(
+                area.x,
+                area.y,
+                area.width,
+                area.height.saturating_sub(3 + 2 + menu_height),
+            ),
+        )
+        .unwrap_or(BoardStyle::Compact);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Length(3),
+                Constraint::Length(1),
+                Constraint::Length(board_style.size(config).1),
+                Constraint::Length(1),
+                Constraint::Length(menu_height),
+                Constraint::Fill(1),
+            ])
+            .split(frame.area());
+
+        let (banner, banner_color) = match game_over_screen.winner {
+            Some(winner) => (format!("{} wins!", MARKS[winner]), theme.players[winner]),
+            None => ("Draw!".to_string(), theme.title),
+        };
+        frame.render_widget(
+            Paragraph::new(Text::styled(
+                banner,
+                Style::default()
+                    .fg(banner_color)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .block(Self::bordered(theme))
+            .centered(),
+            Self::centered_width(MENU_WIDTH, chunks[1]),
+        );
+        frame.render_widget(
+            Paragraph::new(Self::board_lines(
+                &game_over_screen.board,
+                theme,
+                board_style,
+                None,
+                &[],
+                if !animate || animation::flash_on(game_over_screen.finished_at) {
+                    &game_over_screen.winning_line
+                } else {
+                    &[]
+                },
+                &[],
+            ))
+            .centered(),
+            chunks[3],
+        );
+        let note = if game_over_screen.review_pending {
+            "reviewing the game..."
+        } else if game_over_screen.review_unavailable {
+            "review unavailable for this board"
+        } else {
+            ""
+        };
+        frame.render_widget(Paragraph::new(note).centered(), chunks[4]);
+
+        let menu_chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3); GAME_OVER_MENU_NUMS])
+            .split(Self::centered_width(MENU_WIDTH, chunks[5]));
+        game_over_screen.menu_areas = menu_chunks.to_vec();
+        for (index, area) in menu_chunks.iter().enumerate() {
+            let menu = GameOverMenu::try_from(index).unwrap();
+            let mut block = Self::bordered(theme);
+            let mut text = Text::styled(menu.label(), Style::default());
+            if index == game_over_screen.menu_select as usize {
+                text = text.style(
+                    Style::default()
+                        .fg(theme.selection)
+                        .add_modifier(Modifier::BOLD),
+                );
+                block = block.style(Style::default().bg(theme.highlight));
+            }
+            frame.render_widget(Paragraph::new(text).block(block).centered(), *area);
+        }
+    }
+
+    fn review_ui(
+        review_screen: &ReviewScreen,
+        keymap: &Keymap,
+        theme: &Theme,
+        frame: &mut Frame<'_>,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(Self::centered_rect(80, 80, frame.area()));
+
+        let move_lines: Vec<Line> = review_screen
+            .reviews
+            .iter()
+            .enumerate()
+            .map(|(number, review)| {
+                let (row, col) = (
+                    review.index / review_screen.config.cols,
+                    review.index % review_screen.config.cols,
+                );
+                let mut text = format!(
+                    "{:>2}. {} ({}, {})  {} -> {}",
+                    number + 1,
+                    MARKS[review.player],
+                    row,
+                    col,
+                    review.before,
+                    review.after
+                );
+                let mut style = Style::default();
+                if review.is_blunder {
+                    text.push_str("  ?? blunder");
+                    style = style.fg(theme.error);
+                }
+                if number == review_screen.selected {
+                    style = style.bg(theme.highlight).add_modifier(Modifier::BOLD);
+                }
+                Line::styled(text, style)
+            })
+            .collect();
+        let title = format!(
+            "Review ({}/{}: select  {}: menu)",
+            keymap.key(Action::MoveUp),
+            keymap.key(Action::MoveDown),
+            keymap.key(Action::Back)
+        );
+        frame.render_widget(
+            Paragraph::new(move_lines).block(Self::bordered(theme).title(title)),
+            chunks[0],
+        );
+
+        let board = review_screen.selected_board();
+        let last_move = review_screen
+            .reviews
+            .get(review_screen.selected)
+            .map(|review| board.position(review.index));
+        let board_style =
+            BoardStyle::fitting(board.config(), Self::bordered(theme).inner(chunks[1]))
+                .unwrap_or(BoardStyle::Compact);
+        frame.render_widget(
+            Paragraph::new(Self::board_lines(
+                &board,
+                theme,
+                board_style,
+                last_move,
+                &[],
+                &[],
+                &[],
+            ))
+            .block(Self::bordered(theme))
+            .centered(),
+            chunks[1],
+        );
+    }
+
+    fn replay_ui(
+        replay_screen: &ReplayScreen,
+        keymap: &Keymap,
+        theme: &Theme,
+        frame: &mut Frame<'_>,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(Self::centered_rect(80, 80, frame.area()));
 
-            let title_text = Text::from(Span::styled(
-                "Tic-Tac-Toe",
+        let record = &replay_screen.record;
+        let mut move_lines = vec![Line::styled(
+            format!("{}, {} first", record.config, MARKS[record.first_player]),
+            Style::default().add_modifier(Modifier::BOLD),
+        )];
+        let board = replay_screen.board();
+        move_lines.extend(record.moves.iter().enumerate().map(|(number, &index)| {
+            let (row, col) = board.position(index);
+            let player = replay_screen.positions[number].side_to_move();
+            let mut style = Style::default();
+            if number + 1 == replay_screen.shown {
+                style = style.bg(theme.highlight).add_modifier(Modifier::BOLD);
+            }
+            Line::styled(
+                format!("{:>2}. {} ({}, {})", number + 1, MARKS[player], row, col),
+                style,
+            )
+        }));
+        let title = format!(
+            "Replay ({}/{}: step  {}/{}: jump  {}: menu)",
+            keymap.key(Action::MoveLeft),
+            keymap.key(Action::MoveRight),
+            keymap.key(Action::First),
+            keymap.key(Action::Last),
+            keymap.key(Action::Back)
+        );
+        frame.render_widget(
+            Paragraph::new(move_lines).block(Self::bordered(theme).title(title)),
+            chunks[0],
+        );
+
+        let last_move = replay_screen.last_move().map(|index| board.position(index));
+        let board_style =
+            BoardStyle::fitting(board.config(), Self::bordered(theme).inner(chunks[1]))
+                .unwrap_or(BoardStyle::Compact);
+        frame.render_widget(
+            Paragraph::new(Self::board_lines(
+                board,
+                theme,
+                board_style,
+                last_move,
+                &[],
+                &[],
+                &[],
+            ))
+            .block(Self::bordered(theme))
+            .centered(),
+            chunks[1],
+        );
+    }
+
+    fn statistics_ui(
+        statistics_screen: &StatisticsScreen,
+        keymap: &Keymap,
+        theme: &Theme,
+        frame: &mut Frame<'_>,
+    ) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Fill(1),
+                Constraint::Fill(1),
+                Constraint::Length(1),
+            ])
+            .split(Self::centered_rect(80, 80, frame.area()));
+        let statistics = &statistics_screen.statistics;
+
+        let mut player_lines = vec![Line::styled(
+            format!(
+                "{:<16}{:>5}{:>5}{:>5}{:>9}{:>8}{:>11}{:>11}",
+                "Player", "W", "D", "L", "Streak", "Best", "Avg moves", "Avg time"
+            ),
+            Style::default().add_modifier(Modifier::BOLD),
+        )];
+        for (name, player) in &statistics.players {
+            let streak = match player.current_streak {
+                Some(Streak::Wins(n)) => format!("W{n}"),
+                Some(Streak::Draws(n)) => format!("D{n}"),
+                Some(Streak::Losses(n)) => format!("L{n}"),
+                None => "-".to_string(),
+            };
+            player_lines.push(Line::from(format!(
+                "{:<16}{:>5}{:>5}{:>5}{:>9}{:>8}{:>11.1}{:>10.0}s",
+                name,
+                player.wins,
+                player.draws,
+                player.losses,
+                streak,
+                player.best_win_streak,
+                player.average_moves(),
+                player.average_duration_secs()
+            )));
+        }
+        if statistics.players.is_empty() {
+            player_lines.push(Line::from("No games played yet"));
+        }
+        frame.render_widget(
+            Paragraph::new(player_lines).block(Self::bordered(theme).title("Statistics")),
+            chunks[0],
+        );
+
+        let head_to_head_lines: Vec<Line> = statistics
+            .head_to_head
+            .iter()
+            .map(|((first, second), record)| {
+                Line::from(format!(
+                    "{first} vs {second}: {} - {} - {}",
+                    record.wins, record.draws, record.losses
+                ))
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(head_to_head_lines)
+                .block(Self::bordered(theme).title("Head to head (W - D - L)")),
+            chunks[1],
+        );
+
+        let (help, style) = if let Some(error) = &statistics_screen.error {
+            (error.clone(), Style::default().fg(theme.error))
+        } else if statistics_screen.confirm_reset {
+            (
+                format!(
+                    "Reset all statistics? {}: yes  any other key: no",
+                    keymap.key(Action::Confirm)
+                ),
                 Style::default()
-                    .add_modifier(Modifier::BOLD)
-                    .fg(Color::Green),
-            ));
-
-            let title_paragraph_block = Paragraph::new("").block(title_block).centered();
-            let title_area = Self::centered_rect_at(20, 10, 60, 25, frame.area());
-            let title_paragraph_text = Paragraph::new(title_text).centered();
-            frame.render_widget(title_paragraph_block, title_area);
+                    .fg(theme.error)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (
+                format!(
+                    "{}: reset  {}: back",
+                    keymap.key(Action::Reset),
+                    keymap.key(Action::Back)
+                ),
+                Style::default().fg(theme.help),
+            )
+        };
+        frame.render_widget(
+            Paragraph::new(Text::styled(help, style)).centered(),
+            chunks[2],
+        );
+    }
+
+    fn settings_ui(settings_screen: &SettingsScreen, theme: &Theme, frame: &mut Frame<'_>) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Max(SETTINGS_ITEM_NUMS as u16 + 2),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(Self::centered_rect(60, 90, frame.area()));
+        // scrolls just far enough to keep the selected row in view
+        let visible_rows = chunks[0].height.saturating_sub(2).max(1) as usize;
+        let scroll = (settings_screen.selected.index() + 1).saturating_sub(visible_rows);
+
+        let lines: Vec<Line> = (0..SETTINGS_ITEM_NUMS)
+            .map(|index| {
+                let item = SettingsItem::from_index(index);
+                let mut style = Style::default();
+                let value = if item != settings_screen.selected {
+                    item.value(&settings_screen.config)
+                } else {
+                    style = style
+                        .fg(theme.selection)
+                        .bg(theme.highlight)
+                        .add_modifier(Modifier::BOLD);
+                    match (&settings_screen.editing, settings_screen.capturing_key) {
+                        (Some(text), _) => format!("{text}_"),
+                        (None, true) => "press a key...".to_string(),
+                        (None, false) => item.value(&settings_screen.config),
+                    }
+                };
+                Line::styled(format!("{:<28}{value}", item.label()), style)
+            })
+            .collect();
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Self::bordered(theme).title("Settings"))
+                .scroll((scroll as u16, 0)),
+            chunks[0],
+        );
+
+        if let Some(message) = &settings_screen.message {
             frame.render_widget(
-                title_paragraph_text,
-                Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([
-                        Constraint::Fill(1),
-                        Constraint::Length(1),
-                        Constraint::Fill(1),
-                    ])
-                    .split(title_area)[1],
+                Paragraph::new(Text::styled(
+                    message.clone(),
+                    Style::default().fg(theme.error),
+                ))
+                .centered(),
+                chunks[1],
             );
+        }
+        let help = match settings_screen.selected.kind() {
+            SettingsKind::Number | SettingsKind::Choice => "Left/Right: change  Esc: save and back",
+            SettingsKind::Color => "Enter: type a hex color such as #00ff00  Esc: save and back",
+            SettingsKind::Key => "Enter: press the new key  Esc: save and back",
+        };
+        frame.render_widget(
+            Paragraph::new(Text::styled(help, Style::default().fg(theme.help))).centered(),
+            chunks[2],
+        );
+    }
 
-            let menu_chuncks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints(
-                    [
-                        Constraint::Fill(1),
-                        Constraint::Length(3),
-                        Constraint::Length(3),
-                        Constraint::Fill(1),
-                    ]
-                    .as_ref(),
-                )
-                .split(Self::centered_rect_at(20, 50, 60, 35, frame.area()));
-
-            let mut start_block = Block::default().borders(Borders::ALL);
-            let mut quit_block = Block::default().borders(Borders::ALL);
-            let mut start_text = Text::styled("Start Game", Style::default());
-            let mut quit_text = Text::styled("Quit Game", Style::default());
-            match main_screen.menu_select {
-                Some(CurrentSelectMenu::StartGame) => {
-                    start_text = start_text.style(
-                        Style::default()
-                            .fg(Color::LightRed)
-                            .add_modifier(Modifier::BOLD),
-                    );
-                    start_block = start_block.style(Style::default().bg(Color::Gray));
+    fn help_ui(keymap: &Keymap, theme: &Theme, frame: &mut Frame<'_>) {
+        let lines: Vec<Line> = (0..ACTION_NUMS)
+            .map(|index| {
+                let action = Action::try_from(index).unwrap();
+                Line::from(format!(
+                    "{:<16}{}",
+                    action.label(),
+                    keymap.keys(action).join(", ")
+                ))
+            })
+            .collect();
+        // tall enough for every action where the screen allows
+        let area = Self::centered_rect(50, 50, frame.area());
+        let height = (lines.len() as u16 + 2)
+            .max(area.height)
+            .min(frame.area().height);
+        let area = Rect::new(
+            area.x,
+            (frame.area().height - height) / 2,
+            area.width,
+            height,
+        );
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(theme.title))
+                    .style(Style::default().bg(theme.background).fg(theme.text))
+                    .title(format!(
+                        "Keys ({} preset, any key to close)",
+                        keymap.preset.label()
+                    )),
+            ),
+            area,
+        );
+    }
+
+    fn error_ui(error_message: &str, theme: &Theme, frame: &mut Frame<'_>) {
+        let area = Self::centered_rect(60, 30, frame.area());
+        frame.render_widget(Clear, area);
+        frame.render_widget(
+            Paragraph::new(error_message)
+                .wrap(Wrap { trim: true })
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::default().fg(theme.error))
+                        .style(Style::default().bg(theme.background).fg(theme.text))
+                        .title("Error (any key to close)"),
+                ),
+            area,
+        );
+    }
+
+    fn bordered(theme: &Theme) -> Block<'static> {
+        Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border))
+    }
+
+    fn board_lines(
+        board: &BitBoard,
+        theme: &Theme,
+        board_style: BoardStyle,
+        cursor: Option<(usize, usize)>,
+        hint: &[(usize, usize)],
+        winning_line: &[(usize, usize)],
+        // cells whose mark is still appearing, and how far along it is
+        appearing: &[((usize, usize), f32)],
+    ) -> Vec<Line<'static>> {
+        let config = board.config();
+        let border = Style::default().fg(theme.border);
+        let grid_line = |left: &str, middle: &str, right: &str| {
+            Line::styled(
+                format!("{left}{}{right}", vec!["───"; config.cols].join(middle)),
+                border,
+            )
+        };
+        let mut lines = vec![];
+        if board_style == BoardStyle::Boxed {
+            lines.push(grid_line("┌", "┬", "┐"));
+        }
+        for row in 0..config.rows {
+            if row != 0 && board_style == BoardStyle::Boxed {
+                lines.push(grid_line("├", "┼", "┤"));
+            }
+            let mut spans = vec![];
+            for col in 0..config.cols {
+                match board_style {
+                    BoardStyle::Compact if col != 0 => spans.push(Span::raw(" ")),
+                    BoardStyle::Compact => {}
+                    BoardStyle::Boxed => spans.push(Span::styled("│", border)),
                 }
-                Some(CurrentSelectMenu::QuitGame) => {
-                    quit_text = quit_text.style(
-                        Style::default()
-                            .fg(Color::LightRed)
-                            .add_modifier(Modifier::BOLD),
-                    );
-                    quit_block = quit_block.style(Style::default().bg(Color::Gray));
+                let mut style = Style::default();
+                let mark = match board.get(row, col) {
+                    Some(player) => {
+                        style = style.fg(theme.players[player]);
+                        MARKS[player]
+                    }
+                    None if board_style == BoardStyle::Compact => "·",
+                    None => " ",
+                };
+                // a dot that grows into the mark
+                let mark = match appearing.iter().find(|(cell, _)| *cell == (row, col)) {
+                    Some((_, progress)) if *progress < 0.35 => "·",
+                    Some((_, progress)) if *progress < 0.7 => "•",
+                    _ => mark,
+                };
+                if winning_line.contains(&(row, col)) {
+                    style = style.fg(theme.winning_line).add_modifier(Modifier::BOLD);
+                }
+                if hint.contains(&(row, col)) {
+                    style = style.bg(theme.hint);
+                }
+                if cursor == Some((row, col)) {
+                    style = style.bg(theme.cursor);
                 }
-                None => {}
+                spans.push(match board_style {
+                    BoardStyle::Compact => Span::styled(mark, style),
+                    BoardStyle::Boxed => Span::styled(format!(" {mark} "), style),
+                });
             }
-            let start_paragraph = Paragraph::new(start_text).block(start_block).centered();
-            let quit_paragraph = Paragraph::new(quit_text).block(quit_block).centered();
-            frame.render_widget(start_paragraph, menu_chuncks[1]);
-            frame.render_widget(quit_paragraph, menu_chuncks[2]);
+            if board_style == BoardStyle::Boxed {
+                spans.push(Span::styled("│", border));
+            }
+            lines.push(Line::from(spans));
+        }
+        if board_style == BoardStyle::Boxed {
+            lines.push(grid_line("└", "┴", "┘"));
         }
+        lines
     }
 
-    async fn analyze_input(&self) -> Result<(), GameExecutorError> {
+    async fn analyze_input(
+        &self,
+        event: Option<std::io::Result<Event>>,
+    ) -> Result<(), GameExecutorError> {
         debug!("start analyzing input");
-        match *self.current_screen.lock().await {
-            CurrentScreen::Main(ref mut main_screen) => {
-                debug!("match successfully");
-                match self.event_strem.lock().await.next().fuse().await {
-                    Some(Ok(Event::Key(key_event))) => {
-                        if key_event.kind == KeyEventKind::Press {
-                            match key_event.code {
-                                KeyCode::Char('q') | KeyCode::Esc => {
-                                    main_screen.menu_select = Some(CurrentSelectMenu::QuitGame);
-                                }
-                                KeyCode::Up => {
-                                    if let Some(ref mut current_select) = main_screen.menu_select {
-                                        if *current_select as usize != 0 {
-                                            *current_select = CurrentSelectMenu::try_from(
-                                                *current_select as usize - 1,
-                                            )
-                                            .unwrap();
-                                        }
-                                    } else {
-                                        main_screen.menu_select =
-                                            Some(CurrentSelectMenu::StartGame);
-                                    }
-                                }
-                                KeyCode::Down => {
-                                    if let Some(ref mut current_select) = main_screen.menu_select {
-                                        if *current_select as usize != SELECT_MENU_NUMS - 1 {
-                                            *current_select = CurrentSelectMenu::try_from(
-                                                *current_select as usize + 1,
-                                            )
-                                            .unwrap();
-                                        }
-                                    } else {
-                                        main_screen.menu_select =
-                                            Some(CurrentSelectMenu::try_from(0).unwrap());
-                                    }
-                                }
-                                KeyCode::Tab => {
-                                    if let Some(ref mut current_select) = main_screen.menu_select {
-                                        if *current_select as usize != SELECT_MENU_NUMS - 1 {
-                                            *current_select = CurrentSelectMenu::try_from(
-                                                *current_select as usize + 1,
-                                            )
-                                            .unwrap();
-                                        } else {
-                                            *current_select =
-                                                CurrentSelectMenu::try_from(0).unwrap();
-                                        }
-                                    } else {
-                                        main_screen.menu_select =
-                                            Some(CurrentSelectMenu::try_from(0).unwrap());
-                                    }
-                                }
-                                KeyCode::Enter => {
-                                    if let Some(current_select) = main_screen.menu_select {
-                                        match current_select {
-                                            CurrentSelectMenu::StartGame => {
-                                                *self.current_screen.lock().await =
-                                                    CurrentScreen::GameOn(GameOnScreen::new());
-                                            }
-                                            CurrentSelectMenu::QuitGame => {
-                                                *self.quit_game_now.lock().await = true;
-                                            }
-                                        }
-                                    }
-                                }
-                                _ => {}
-                            }
+        let key_event = match event {
+            Some(Ok(Event::Key(key_event))) if key_event.kind == KeyEventKind::Press => key_event,
+            Some(Ok(Event::Mouse(mouse_event))) => {
+                return self.analyze_mouse_input(mouse_event).await;
+            }
+            // the next draw picks the layout for the new size; clearing drops
+            // whatever the terminal left behind while resizing
+            Some(Ok(Event::Resize(..))) => {
+                self.terminal.lock().await.clear()?;
+                return Ok(());
+            }
+            Some(Ok(_)) | None => return Ok(()),
+            Some(Err(err)) => {
+                return Err(GameExecutorError::from(err));
+            }
+        };
+        // raw mode turns Ctrl-C into a key press instead of SIGINT
+        if key_event.code == KeyCode::Char('c')
+            && key_event.modifiers.contains(KeyModifiers::CONTROL)
+        {
+            self.cancel_token.cancel();
+            return Ok(());
+        }
+        if self.error_message.lock().await.take().is_some() {
+            return Ok(());
+        }
+        let key_code = key_event.code;
+        let action = self.config.lock().await.keymap.action(key_code);
+        let mut current_screen = self.current_screen.lock().await;
+        {
+            let mut show_help = self.show_help.lock().await;
+            if *show_help {
+                *show_help = false;
+                return Ok(());
+            }
+            if !Self::captures_keys(&current_screen) {
+                match action {
+                    Some(Action::Help) => {
+                        *show_help = true;
+                        return Ok(());
+                    }
+                    // the settings screen saves before quitting
+                    Some(Action::Quit)
+                        if !matches!(*current_screen, CurrentScreen::Settings(_)) =>
+                    {
+                        *self.quit_game_now.lock().await = true;
+                        return Ok(());
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let next_screen = match &mut *current_screen {
+            CurrentScreen::Main(main_screen) => {
+                self.analyze_main_input(main_screen, action).await?
+            }
+            CurrentScreen::GameOn(game_on_screen) => {
+                self.analyze_game_on_input(game_on_screen, action).await?
+            }
+            CurrentScreen::GameOver(game_over_screen) => {
+                self.analyze_game_over_input(game_over_screen, action)
+                    .await?
+            }
+            CurrentScreen::Review(review_screen) => {
+                Self::analyze_review_input(review_screen, action)
+            }
+            CurrentScreen::Statistics(statistics_screen) => {
+                self.analyze_statistics_input(statistics_screen, action)
+            }
+            CurrentScreen::Settings(settings_screen) => {
+                self.analyze_settings_input(settings_screen, action, key_code)
+                    .await
+            }
+            CurrentScreen::Replay(replay_screen) => {
+                Self::analyze_replay_input(replay_screen, action)
+            }
+        };
+        if let Some(next_screen) = next_screen {
+            *current_screen = next_screen;
+        }
+        Ok(())
+    }
+
+    // hovering selects a menu entry and clicking acts like placing on it
+    async fn analyze_mouse_input(&self, mouse_event: MouseEvent) -> Result<(), GameExecutorError> {
+        let (column, row) = (mouse_event.column, mouse_event.row);
+        let is_click = match mouse_event.kind {
+            MouseEventKind::Down(MouseButton::Left) => true,
+            MouseEventKind::Moved => false,
+            _ => return Ok(()),
+        };
+        if *self.show_help.lock().await {
+            if is_click {
+                *self.show_help.lock().await = false;
+            }
+            return Ok(());
+        }
+        {
+            let mut error_message = self.error_message.lock().await;
+            if error_message.is_some() {
+                if is_click {
+                    *error_message = None;
+                }
+                return Ok(());
+            }
+        }
+        let mut current_screen = self.current_screen.lock().await;
+        let next_screen = match &mut *current_screen {
+            CurrentScreen::Main(main_screen) => match main_screen.menu_at(column, row) {
+                Some(menu) => {
+                    main_screen.menu_select = Some(menu);
+                    if !is_click {
+                        return Ok(());
+                    }
+                    self.analyze_main_input(main_screen, Some(Action::Place))
+                        .await?
+                }
+                None => None,
+            },
+            CurrentScreen::GameOver(game_over_screen) => {
+                match game_over_screen.menu_at(column, row) {
+                    Some(menu) => {
+                        game_over_screen.menu_select = menu;
+                        if !is_click {
+                            return Ok(());
                         }
+                        self.analyze_game_over_input(game_over_screen, Some(Action::Place))
+                            .await?
                     }
-                    Some(Ok(_)) => {}
-                    Some(Err(err)) => {
-                        return Err(GameExecutorError::from(err));
+                    None => None,
+                }
+            }
+            CurrentScreen::GameOn(game_on_screen) if is_click => {
+                match game_on_screen.cell_at(column, row) {
+                    Some(cell) => {
+                        game_on_screen.cursor = cell;
+                        self.analyze_game_on_input(game_on_screen, Some(Action::Place))
+                            .await?
                     }
-                    None => {}
-                };
-                Ok(())
-            }
-            CurrentScreen::GameOn(ref mut game_on_screen) => {
-                match self.event_strem.lock().await.next().fuse().await {
-                    Some(Ok(Event::Key(key_event))) => {
-                        if let KeyEventKind::Press = key_event.kind {
-                            match key_event.code {
-                                KeyCode::Up => {}
-                                _ => {}
-                            }
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        if let Some(next_screen) = next_screen {
+            *current_screen = next_screen;
+        }
+        Ok(())
+    }
+
+    // screens that read raw keys, where actions such as help and quit are off
+    fn captures_keys(current_screen: &CurrentScreen) -> bool {
+        match current_screen {
+            CurrentScreen::Settings(settings_screen) => {
+                settings_screen.editing.is_some() || settings_screen.capturing_key
+            }
+            CurrentScreen::Statistics(statistics_screen) => statistics_screen.confirm_reset,
+            _ => false,
+        }
+    }
+
+    async fn analyze_main_input(
+        &self,
+        main_screen: &mut MainScreen,
+        action: Option<Action>,
+    ) -> Result<Option<CurrentScreen>, GameExecutorError> {
+        let Some(action) = action else {
+            return Ok(None);
+        };
+        match action {
+            Action::Back => {
+                main_screen.menu_select = Some(CurrentSelectMenu::QuitGame);
+            }
+            Action::MoveUp => {
+                if let Some(ref mut current_select) = main_screen.menu_select {
+                    if *current_select as usize != 0 {
+                        *current_select =
+                            CurrentSelectMenu::try_from(*current_select as usize - 1).unwrap();
+                    }
+                } else {
+                    main_screen.menu_select = Some(CurrentSelectMenu::StartGame);
+                }
+            }
+            Action::MoveDown => {
+                if let Some(ref mut current_select) = main_screen.menu_select {
+                    if *current_select as usize != SELECT_MENU_NUMS - 1 {
+                        *current_select =
+                            CurrentSelectMenu::try_from(*current_select as usize + 1).unwrap();
+                    }
+                } else {
+                    main_screen.menu_select = Some(CurrentSelectMenu::try_from(0).unwrap());
+                }
+            }
+            Action::Place => {
+                if let Some(current_select) = main_screen.menu_select {
+                    match current_select {
+                        CurrentSelectMenu::StartGame => {
+                            let board_config = self.config.lock().await.board;
+                            return Ok(Some(self.start_game(board_config, 0).await?));
+                        }
+                        CurrentSelectMenu::Statistics => {
+                            return Ok(Some(CurrentScreen::Statistics(StatisticsScreen::new(
+                                self.load_statistics(),
+                            ))));
+                        }
+                        CurrentSelectMenu::Settings => {
+                            return Ok(Some(CurrentScreen::Settings(SettingsScreen::new(
+                                self.config.lock().await.clone(),
+                            ))));
+                        }
+                        CurrentSelectMenu::QuitGame => {
+                            *self.quit_game_now.lock().await = true;
                         }
                     }
-                    Some(Ok(_)) => {}
-                    Some(Err(err)) => {
-                        return Err(GameExecutorError::from(err));
+                }
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    async fn analyze_game_on_input(
+        &self,
+        game_on_screen: &mut GameOnScreen,
+        action: Option<Action>,
+    ) -> Result<Option<CurrentScreen>, GameExecutorError> {
+        match action {
+            Some(Action::MoveUp) => game_on_screen.move_cursor(-1, 0),
+            Some(Action::MoveDown) => game_on_screen.move_cursor(1, 0),
+            Some(Action::MoveLeft) => game_on_screen.move_cursor(0, -1),
+            Some(Action::MoveRight) => game_on_screen.move_cursor(0, 1),
+            Some(Action::Place) => {
+                let (row, col) = game_on_screen.cursor;
+                self.submit_move(game_on_screen, row, col).await?;
+            }
+            Some(Action::Undo) => self.undo_move(game_on_screen).await?,
+            Some(Action::Hint)
+                if !game_on_screen.is_over()
+                    && !game_on_screen.ai_thinking
+                    && !game_on_screen.hint_pending =>
+            {
+                game_on_screen.hint_pending = true;
+                let (analyzer, board) = (self.analyzer.clone(), game_on_screen.board.clone());
+                let game_id = self.get_tx()?.game_id();
+                self.search(move || {
+                    let mut analyzer = analyzer.blocking_lock();
+                    let best_moves = analyzer
+                        .can_solve(board.config())
+                        .then(|| analyzer.best_moves(&board));
+                    ManagerEvent::Hint(game_id, board, best_moves)
+                });
+            }
+            Some(Action::Back) => {
+                return Ok(Some(CurrentScreen::Main(MainScreen::new())));
+            }
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    // against the AI its reply is taken back too, so it is the player's turn
+    // again; a move leaves the screen only once the manager has taken it
+    // back, and after a failed undo the screen is rebuilt from the
+    // manager's board
+    async fn undo_move(&self, game_on_screen: &mut GameOnScreen) -> Result<(), GameExecutorError> {
+        let count = if self.ai.is_some() { 2 } else { 1 };
+        if game_on_screen.pending_move.is_some()
+            || game_on_screen.ai_thinking
+            || game_on_screen.moves.len() < count
+        {
+            return Ok(());
+        }
+        for _ in 0..count {
+            let Some(&index) = game_on_screen.moves.last() else {
+                break;
+            };
+            let (row, col) = game_on_screen.board.position(index);
+            let reply = self
+                .request(ExecutorToManagerMsg::Request(
+                    ExecutorToManagerReqMsg::UndoMoveRequest(row, col),
+                ))
+                .await;
+            if let Err(err) = reply {
+                match self.get_state().await {
+                    Ok(snapshot) => {
+                        game_on_screen
+                            .rebuild(&snapshot)
+                            .map_err(anyhow::Error::from)?;
                     }
-                    None => (),
+                    Err(err) => warn!("could not fetch the board after a failed undo: {err}"),
                 }
-                Ok(())
+                return Err(err);
             }
+            game_on_screen.undo();
         }
+        Ok(())
     }
 
-    fn centered_rect_at(
-        start_x: u16,
-        start_y: u16,
-        percent_x: u16,
-        percent_y: u16,
-        rect: Rect,
-    ) -> Rect {
-        let popup_layout = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints(
-                [
-                    Constraint::Percentage(start_y),
-                    Constraint::Percentage(percent_y),
-                    Constraint::Percentage(100 - percent_y - start_y),
-                ]
-                .as_ref(),
-            )
-            .split(rect);
+    // shows the mark at once and sends it to the manager, whose verdict
+    // arrives later as a ManagerEvent
+    async fn submit_move(
+        &self,
+        game_on_screen: &mut GameOnScreen,
+        row: usize,
+        col: usize,
+    ) -> Result<(), GameExecutorError> {
+        if game_on_screen.pending_move.is_some() || game_on_screen.ai_thinking {
+            return Ok(());
+        }
+        let Some(player) = game_on_screen.place(row, col) else {
+            return Ok(());
+        };
+        game_on_screen.pending_move = Some(player);
+        let (tx, manager_events) = (self.get_tx()?, self.manager_events_tx.clone());
+        tokio::spawn(async move {
+            let game_id = tx.game_id();
+            let reply = tx
+                .request(ExecutorToManagerMsg::Request(
+                    ExecutorToManagerReqMsg::TileOnByPlayerRequesst(
+                        Player { id: player },
+                        row,
+                        col,
+                    ),
+                ))
+                .await;
+            let event = match reply.map(|reply| reply.body) {
+                Ok(ManagerToExecutorMsg::Response(
+                    ManagerToExecutorResMsg::TileOnByPlayerResponse(is_win),
+                )) => ManagerEvent::MoveResult(game_id, is_win),
+                Ok(ManagerToExecutorMsg::ErrorNotification(notification)) => {
+                    ManagerEvent::MoveFailed(
+                        game_id,
+                        GameExecutorError::Refused(notification.message),
+                    )
+                }
+                Ok(body) => {
+                    warn!("unexpected reply to a move: {body:?}");
+                    return;
+                }
+                Err(err) => ManagerEvent::MoveFailed(game_id, err.into()),
+            };
+            // the UI loop is gone once the game quits
+            let _ = manager_events.send(event).await;
+        });
+        Ok(())
+    }
 
-        Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(
-                [
-                    Constraint::Percentage(start_x),
-                    Constraint::Percentage(percent_x),
-                    Constraint::Percentage(100 - percent_x - start_x),
-                ]
-                .as_ref(),
-            )
-            .split(popup_layout[1])[1]
+    // the AI thinks off the UI task; its move arrives as a ManagerEvent
+    async fn play_ai_move(
+        &self,
+        game_on_screen: &mut GameOnScreen,
+    ) -> Result<(), GameExecutorError> {
+        let Some(ai) = &self.ai else {
+            return Ok(());
+        };
+        if game_on_screen.ai_thinking || game_on_screen.board.side_to_move() != AI_PLAYER {
+            return Ok(());
+        }
+        game_on_screen.ai_thinking = true;
+        let (ai, analyzer) = (ai.clone(), self.analyzer.clone());
+        let (game_id, board) = (self.get_tx()?.game_id(), game_on_screen.board.clone());
+        self.search(move || {
+            let mut analyzer = analyzer.blocking_lock();
+            let index = ai.blocking_lock().choose_move(&board, &mut analyzer);
+            ManagerEvent::AiMove(game_id, board, index)
+        });
+        Ok(())
+    }
+
+    // runs a search on a blocking thread, so the UI loop keeps drawing and
+    // taking input; the loop hears of the result as the event it returns
+    fn search(&self, search: impl FnOnce() -> ManagerEvent + Send + 'static) {
+        let manager_events = self.manager_events_tx.clone();
+        tokio::spawn(async move {
+            let event = tokio::task::spawn_blocking(search)
+                .await
+                .unwrap_or_else(|err| ManagerEvent::Error(err.into()));
+            // the UI loop is gone once the game quits
+            let _ = manager_events.send(event).await;
+        });
+    }
+
+    async fn analyze_manager_event(&self, event: ManagerEvent) -> Result<(), GameExecutorError> {
+        let mut current_screen = self.current_screen.lock().await;
+        match (event, &mut *current_screen) {
+            (ManagerEvent::MoveFailed(game_id, err), current_screen) => {
+                // the mark shown for the move goes again
+                if let CurrentScreen::GameOn(game_on_screen) = current_screen {
+                    if game_id == self.get_tx()?.game_id()
+                        && game_on_screen.pending_move.take().is_some()
+                    {
+                        game_on_screen.undo();
+                    }
+                }
+                return Err(err);
+            }
+            (ManagerEvent::Error(err), _) => return Err(err),
+            (ManagerEvent::MoveResult(game_id, is_win), CurrentScreen::GameOn(game_on_screen)) => {
+                // a verdict for a game that was left already
+                if game_id != self.get_tx()?.game_id() {
+                    return Ok(());
+                }
+                let Some(player) = game_on_screen.pending_move.take() else {
+                    return Ok(());
+                };
+                if is_win || game_on_screen.is_over() {
+                    let game_over_screen =
+                        GameOverScreen::new(game_on_screen.clone(), is_win.then_some(player));
+                    self.record_game(&game_over_screen);
+                    *current_screen = CurrentScreen::GameOver(game_over_screen);
+                } else {
+                    self.play_ai_move(game_on_screen).await?;
+                }
+            }
+            (ManagerEvent::Snapshot(game_id, snapshot), CurrentScreen::GameOn(game_on_screen)) => {
+                if game_id != self.get_tx()?.game_id() {
+                    return Ok(());
+                }
+                if game_on_screen
+                    .resync(&snapshot)
+                    .map_err(anyhow::Error::from)?
+                {
+                    warn!("the board drifted from the manager's and was resynced");
+                }
+            }
+            (
+                ManagerEvent::AiMove(game_id, board, index),
+                CurrentScreen::GameOn(game_on_screen),
+            ) => {
+                if game_id != self.get_tx()?.game_id() || !game_on_screen.ai_thinking {
+                    return Ok(());
+                }
+                game_on_screen.ai_thinking = false;
+                // the board changed while the AI thought, e.g. in a resync
+                if game_on_screen.board != board {
+                    return self.play_ai_move(game_on_screen).await;
+                }
+                if let Some(index) = index {
+                    let (row, col) = board.position(index);
+                    self.submit_move(game_on_screen, row, col).await?;
+                }
+            }
+            (
+                ManagerEvent::Hint(game_id, board, best_moves),
+                CurrentScreen::GameOn(game_on_screen),
+            ) => {
+                // a hint for a board that has changed since is dropped
+                if game_id != self.get_tx()?.game_id()
+                    || !game_on_screen.hint_pending
+                    || game_on_screen.board != board
+                {
+                    return Ok(());
+                }
+                game_on_screen.hint_pending = false;
+                let Some(best_moves) = best_moves else {
+                    game_on_screen.hint_unavailable = true;
+                    return Ok(());
+                };
+                game_on_screen.hint_value = best_moves.first().map(|best| best.value);
+                game_on_screen.hint = best_moves.iter().map(|best| (best.row, best.col)).collect();
+            }
+            (
+                ManagerEvent::Review(game_id, moves, reviews),
+                CurrentScreen::GameOver(game_over_screen),
+            ) => {
+                if game_id != self.get_tx()?.game_id()
+                    || !game_over_screen.review_pending
+                    || game_over_screen.moves != moves
+                {
+                    return Ok(());
+                }
+                game_over_screen.review_pending = false;
+                let Some(reviews) = reviews else {
+                    game_over_screen.review_unavailable = true;
+                    return Ok(());
+                };
+                let review_screen = ReviewScreen::new(
+                    game_over_screen.board.config(),
+                    game_over_screen.first_player,
+                    reviews.map_err(anyhow::Error::from)?,
+                );
+                *current_screen = CurrentScreen::Review(review_screen);
+            }
+            (ManagerEvent::PlayerWon(player, winning_line), current_screen) => {
+                info!("player {} Win: {:?}", player.id, winning_line);
+                // the manager's line is the one that counts
+                match current_screen {
+                    CurrentScreen::GameOn(GameOnScreen {
+                        winning_line: line, ..
+                    })
+                    | CurrentScreen::GameOver(GameOverScreen {
+                        winning_line: line, ..
+                    }) => *line = winning_line,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    async fn start_game(
+        &self,
+        board_config: BoardConfig,
+        first_player: usize,
+    ) -> Result<CurrentScreen, GameExecutorError> {
+        self.reset_game(board_config, first_player).await?;
+        let mut game_on_screen = GameOnScreen::new(board_config, first_player);
+        if let Some(ai) = &self.ai {
+            game_on_screen.players[AI_PLAYER] = ai.lock().await.name();
+        }
+        self.play_ai_move(&mut game_on_screen).await?;
+        Ok(CurrentScreen::GameOn(game_on_screen))
+    }
+
+    async fn analyze_game_over_input(
+        &self,
+        game_over_screen: &mut GameOverScreen,
+        action: Option<Action>,
+    ) -> Result<Option<CurrentScreen>, GameExecutorError> {
+        let current_select = game_over_screen.menu_select as usize;
+        match action {
+            Some(Action::MoveUp) => {
+                game_over_screen.menu_select =
+                    GameOverMenu::try_from(current_select.saturating_sub(1)).unwrap();
+            }
+            Some(Action::MoveDown) => {
+                game_over_screen.menu_select =
+                    GameOverMenu::try_from((current_select + 1).min(GAME_OVER_MENU_NUMS - 1))
+                        .unwrap();
+            }
+            Some(Action::Back) => {
+                return Ok(Some(CurrentScreen::Main(MainScreen::new())));
+            }
+            Some(Action::Place) => match game_over_screen.menu_select {
+                GameOverMenu::Rematch => {
+                    return Ok(Some(
+                        self.start_game(
+                            game_over_screen.board.config(),
+                            (game_over_screen.first_player + 1) % PLAYER_NUM,
+                        )
+                        .await?,
+                    ));
+                }
+                GameOverMenu::Review if !game_over_screen.review_pending => {
+                    game_over_screen.review_pending = true;
+                    let analyzer = self.analyzer.clone();
+                    let config = game_over_screen.board.config();
+                    let (first_player, moves) = (
+                        game_over_screen.first_player,
+                        game_over_screen.moves.clone(),
+                    );
+                    let game_id = self.get_tx()?.game_id();
+                    self.search(move || {
+                        let mut analyzer = analyzer.blocking_lock();
+                        let reviews = analyzer
+                            .can_solve(config)
+                            .then(|| analyzer.review(config, first_player, &moves));
+                        ManagerEvent::Review(game_id, moves, reviews)
+                    });
+                }
+                GameOverMenu::Review => {}
+                GameOverMenu::MainMenu => {
+                    return Ok(Some(CurrentScreen::Main(MainScreen::new())));
+                }
+                GameOverMenu::Quit => {
+                    *self.quit_game_now.lock().await = true;
+                }
+            },
+            _ => {}
+        }
+        Ok(None)
+    }
+
+    fn analyze_statistics_input(
+        &self,
+        statistics_screen: &mut StatisticsScreen,
+        action: Option<Action>,
+    ) -> Option<CurrentScreen> {
+        if statistics_screen.confirm_reset {
+            statistics_screen.confirm_reset = false;
+            if action == Some(Action::Confirm) {
+                if let Some(stats_store) = &self.stats_store {
+                    if let Err(err) = stats_store.reset() {
+                        statistics_screen.error = Some(format!("Failed to reset: {err}"));
+                        return None;
+                    }
+                }
+                statistics_screen.statistics = Statistics::default();
+            }
+            return None;
+        }
+        match action {
+            Some(Action::Reset) => statistics_screen.confirm_reset = true,
+            Some(Action::Back) => {
+                return Some(CurrentScreen::Main(MainScreen::new()));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    async fn analyze_settings_input(
+        &self,
+        settings_screen: &mut SettingsScreen,
+        action: Option<Action>,
+        key_code: KeyCode,
+    ) -> Option<CurrentScreen> {
+        if settings_screen.capturing_key {
+            settings_screen.capturing_key = false;
+            if key_code != KeyCode::Esc {
+                match key_name(key_code) {
+                    Some(name) => settings_screen.commit_key(name),
+                    None => settings_screen.message = Some("Key cannot be bound".to_string()),
+                }
+            }
+            return None;
+        }
+        if let Some(text) = &mut settings_screen.editing {
+            match key_code {
+                KeyCode::Char(c) => text.push(c),
+                KeyCode::Backspace => {
+                    text.pop();
+                }
+                KeyCode::Enter => {
+                    let hex = text.clone();
+                    settings_screen.editing = None;
+                    settings_screen.commit_color(hex);
+                }
+                KeyCode::Esc => settings_screen.editing = None,
+                _ => {}
+            }
+            return None;
+        }
+        match action {
+            Some(Action::MoveUp) => settings_screen.select(-1),
+            Some(Action::MoveDown) => settings_screen.select(1),
+            Some(Action::MoveLeft) => settings_screen.adjust(-1),
+            Some(Action::MoveRight) => settings_screen.adjust(1),
+            Some(Action::Place) => match settings_screen.selected.kind() {
+                SettingsKind::Color => settings_screen.editing = Some(String::new()),
+                SettingsKind::Key => settings_screen.capturing_key = true,
+                SettingsKind::Number | SettingsKind::Choice => {}
+            },
+            Some(action @ (Action::Back | Action::Quit)) => {
+                // changes apply right away and are saved on the way out
+                *self.config.lock().await = settings_screen.config.clone();
+                if let Some(config_path) = &self.config_path {
+                    if let Err(err) = settings_screen.config.save(config_path) {
+                        warn!("failed to save config: {err}");
+                    }
+                }
+                if action == Action::Quit {
+                    *self.quit_game_now.lock().await = true;
+                }
+                return Some(CurrentScreen::Main(MainScreen::new()));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn analyze_replay_input(
+        replay_screen: &mut ReplayScreen,
+        action: Option<Action>,
+    ) -> Option<CurrentScreen> {
+        match action {
+            Some(Action::First) => replay_screen.shown = 0,
+            Some(Action::Last) => replay_screen.shown = replay_screen.record.moves.len(),
+            Some(Action::MoveLeft | Action::MoveUp) => replay_screen.step(-1),
+            Some(Action::MoveRight | Action::MoveDown) => replay_screen.step(1),
+            Some(Action::Back) => {
+                return Some(CurrentScreen::Main(MainScreen::new()));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn analyze_review_input(
+        review_screen: &mut ReviewScreen,
+        action: Option<Action>,
+    ) -> Option<CurrentScreen> {
+        match action {
+            Some(Action::MoveUp | Action::MoveLeft) => review_screen.select_previous(),
+            Some(Action::MoveDown | Action::MoveRight) => review_screen.select_next(),
+            Some(Action::Back) => {
+                return Some(CurrentScreen::Main(MainScreen::new()));
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn record_game(&self, game_over_screen: &GameOverScreen) {
+        if let Some(stats_store) = &self.stats_store {
+            if let Err(err) = stats_store.record(&game_over_screen.result()) {
+                warn!("failed to record game: {err}");
+            }
+        }
+        if let Some(record_path) = &self.record_path {
+            if let Err(err) = game_over_screen.record().save(record_path) {
+                warn!("failed to save game to {}: {err}", record_path.display());
+            }
+        }
+    }
+
+    fn load_statistics(&self) -> Statistics {
+        let Some(stats_store) = &self.stats_store else {
+            return Statistics::default();
+        };
+        match stats_store.load() {
+            Ok(results) => Statistics::from_results(&results),
+            Err(err) => {
+                warn!("failed to load statistics: {err}");
+                Statistics::default()
+            }
+        }
+    }
+
+    // moves sent from now on belong to the game the manager has just started
+    async fn reset_game(
+        &self,
+        board_config: BoardConfig,
+        first_player: usize,
+    ) -> Result<(), GameExecutorError> {
+        let reply = self
+            .request(ExecutorToManagerMsg::Request(
+                ExecutorToManagerReqMsg::ResetGameRequest(
+                    board_config,
+                    Player { id: first_player },
+                ),
+            ))
+            .await?;
+        self.get_tx()?.enter_game(reply.game_id);
+        Ok(())
+    }
+
+    // at most `width` wide, centered horizontally
+    fn centered_width(width: u16, rect: Rect) -> Rect {
+        let [_, middle, _] = Layout::horizontal([
+            Constraint::Fill(1),
+            Constraint::Length(width.min(rect.width)),
+            Constraint::Fill(1),
+        ])
+        .areas(rect);
+        middle
     }
 
     fn centered_rect(percent_x: u16, percent_y: u16, rect: Rect) -> Rect {
@@ -310,87 +1810,35 @@ impl<B: Backend + std::marker::Send + std::marker::Sync> TuiGameExecutor<B> {
     }
 }
 
-// 新增函数：使用 ratatui 读取两个整数 posx 和 posy
-async fn read_pos_from_tui<B: Backend>(
-    terminal: Arc<Mutex<Terminal<B>>>,
-) -> Result<(usize, usize), GameExecutorError> {
-    let mut posx = String::new();
-    let mut posy = String::new();
-    let mut focus_on_posx = true;
-
-    loop {
-        terminal
-            .lock()
-            .await
-            .draw(|f| {
-                let chunks = Layout::default()
-                    .direction(Direction::Vertical)
-                    .constraints([Constraint::Length(3), Constraint::Length(3)].as_ref())
-                    .split(f.size());
-
-                let input_style = Style::default().fg(Color::White).bg(Color::Black);
-                let focused_style = Style::default().fg(Color::Black).bg(Color::White);
-
-                let posx_input =
-                    Paragraph::new(format!("posx: {}", posx)).style(if focus_on_posx {
-                        focused_style
-                    } else {
-                        input_style
-                    });
-                let posy_input =
-                    Paragraph::new(format!("posy: {}", posy)).style(if !focus_on_posx {
-                        focused_style
-                    } else {
-                        input_style
-                    });
-
-                f.render_widget(posx_input, chunks[0]);
-                f.render_widget(posy_input, chunks[1]);
-            })
-            .context("Failed to draw UI")?;
-
-        if let Event::Key(KeyEvent {
-            code,
-            modifiers,
-            kind,
-            state,
-        }) = event::read().context("Failed to read event from terminal")?
-        {
-            match (code, modifiers) {
-                (KeyCode::Char(c), _) if focus_on_posx => posx.push(c),
-                (KeyCode::Char(c), _) if !focus_on_posx => posy.push(c),
-                (KeyCode::Backspace, _) if focus_on_posx && !posx.is_empty() => {
-                    posx.pop();
-                }
-                (KeyCode::Backspace, _) if !focus_on_posx && !posy.is_empty() => {
-                    posy.pop();
-                }
-                (KeyCode::Tab, _) => focus_on_posx = !focus_on_posx,
-                (KeyCode::Enter, _) => {
-                    let posx_val = posx.parse::<usize>().unwrap_or(0);
-                    let posy_val = posy.parse::<usize>().unwrap_or(0);
-                    return Ok((posx_val, posy_val));
-                }
-                _ => {}
-            }
-        }
-    }
-}
-
 #[async_trait]
 impl<B: Backend + std::marker::Sync + std::marker::Send> GameExecutor for TuiGameExecutor<B> {
-    fn get_tx(&self) -> Result<Arc<Mutex<mpsc::Sender<ExecutorToManagerMsg>>>, GameExecutorError> {
-        Ok(self.tx.clone().unwrap())
+    fn get_tx(&self) -> Result<Arc<ExecutorOutbox>, GameExecutorError> {
+        self.tx.clone().ok_or(GameExecutorError::GetTxError)
     }
 
     fn get_rx(
         &self,
-    ) -> Result<Arc<Mutex<mpsc::Receiver<ManagerToExecutorMsg>>>, GameExecutorError> {
-        Ok(self.rx.clone().unwrap())
+    ) -> Result<Arc<Mutex<mpsc::Receiver<Envelope<ManagerToExecutorMsg>>>>, GameExecutorError> {
+        self.rx.clone().ok_or(GameExecutorError::GetRxError)
+    }
+
+    fn get_cancel_token(&self) -> CancellationToken {
+        self.cancel_token.clone()
     }
 
     async fn init_game_impl(&self) -> Result<(), GameExecutorError> {
         trace!("init game");
+        let screen = match &self.launch {
+            Launch::Menu => return Ok(()),
+            Launch::Play => {
+                let board_config = self.config.lock().await.board;
+                self.start_game(board_config, 0).await?
+            }
+            Launch::Replay(record) => CurrentScreen::Replay(
+                ReplayScreen::new(record.clone()).map_err(anyhow::Error::from)?,
+            ),
+        };
+        *self.current_screen.lock().await = screen;
         Ok(())
     }
 
@@ -402,32 +1850,100 @@ impl<B: Backend + std::marker::Sync + std::marker::Send> GameExecutor for TuiGam
     async fn execute_game_impl(&self) -> Result<(), GameExecutorError> {
         let tx = self.get_tx()?;
         trace!("start executing game");
+        let mut event_stream = self.event_strem.lock().await;
+        let mut manager_events = self.manager_events_rx.lock().await;
         loop {
             let mut current_screen = self.current_screen.lock().await;
+            let config = self.config.lock().await.clone();
+            let show_help = *self.show_help.lock().await;
+            let error_message = self.error_message.lock().await.clone();
+            let shown_at = {
+                let mut screen_shown = self.screen_shown.lock().await;
+                let kind = mem::discriminant(&*current_screen);
+                if screen_shown.0 != kind {
+                    *screen_shown = (kind, Instant::now());
+                }
+                screen_shown.1
+            };
             self.terminal.lock().await.draw(|frame| {
-                Self::ui(&mut current_screen, frame);
+                Self::ui(
+                    &mut current_screen,
+                    &config,
+                    show_help,
+                    error_message.as_deref(),
+                    shown_at,
+                    frame,
+                );
             })?;
             debug!("drawing");
+            let redraw_after = Self::redraw_after(&current_screen, &config, shown_at);
             drop(current_screen);
-            self.analyze_input().await?;
+            let result = tokio::select! {
+                event = event_stream.next() => self.analyze_input(event).await,
+                Some(event) = manager_events.recv() => self.analyze_manager_event(event).await,
+                _ = tokio::time::sleep(redraw_after.unwrap_or_default()), if redraw_after.is_some() => Ok(()),
+                _ = self.cancel_token.cancelled() => {
+                    *self.quit_game_now.lock().await = true;
+                    Ok(())
+                }
+            };
+            match result {
+                Err(err) if !err.is_fatal() => {
+                    warn!("{err}");
+                    *self.error_message.lock().await = Some(err.to_string());
+                }
+                result => result?,
+            }
             debug!("finalize input");
             if *self.quit_game_now.lock().await {
                 break;
             }
             debug!("complete one loop");
         }
-        tx.lock()
-            .await
-            .send(ExecutorToManagerMsg::Request(
-                ExecutorToManagerReqMsg::ReadyToQuitGameRequest,
+        tx.send(ExecutorToManagerMsg::Request(
+            ExecutorToManagerReqMsg::ReadyToQuitGameRequest,
+        ))
+        .await?;
+        Ok(())
+    }
+
+    async fn player_win_impl(
+        &self,
+        player: &Player,
+        winning_line: &[(usize, usize)],
+    ) -> Result<(), GameExecutorError> {
+        self.manager_events_tx
+            .send(ManagerEvent::PlayerWon(
+                player.clone(),
+                winning_line.to_vec(),
             ))
-            .await?;
+            .await
+            .map_err(|_| GameExecutorError::MessageErr)?;
+        Ok(())
+    }
+
+    async fn process_state_snapshot(
+        &self,
+        game_id: u64,
+        snapshot: StateSnapshot,
+    ) -> Result<(), GameExecutorError> {
+        self.manager_events_tx
+            .send(ManagerEvent::Snapshot(game_id, snapshot))
+            .await
+            .map_err(|_| GameExecutorError::MessageErr)?;
         Ok(())
     }
 
-    async fn player_win_impl(&self, player: &Player) -> Result<(), GameExecutorError> {
-        self.is_win_notify.notify_one();
-        info!("player {} Win", player.id);
+    async fn process_error_notification(
+        &self,
+        notification: ErrorNotification,
+    ) -> Result<(), GameExecutorError> {
+        self.manager_events_tx
+            .send(ManagerEvent::Error(GameExecutorError::Refused(
+                notification.message,
+            )))
+            .await
+            .map_err(|_| GameExecutorError::MessageErr)?;
         Ok(())
     }
 
@@ -435,66 +1951,36 @@ impl<B: Backend + std::marker::Sync + std::marker::Send> GameExecutor for TuiGam
         &self,
         is_win: bool,
     ) -> Result<(), GameExecutorError> {
-        trace!("process tile on by player response");
-        *self.is_win.lock().await = is_win;
-        self.is_tile_on_notify.notify_one();
+        // the request that sent the move has given up on it already
+        warn!("late result for a move: {is_win}");
         Ok(())
     }
 }
-// struct Reader<'a> {
-//     buf: *mut String,
-//     nums: Option<SplitWhitespace<'a>>,
-// }
-
-// impl Reader<'_> {
-//     fn new() -> Self {
-//         Self {
-//             buf: std::ptr::null_mut(),
-//             nums: None,
-//         }
-//     }
-
-//     fn get_line(&mut self) {
-//         let mut new_line = Box::new(String::new());
-//         std::io::stdin().read_line(&mut new_line).unwrap();
-//         if !self.buf.is_null() {
-//             let _ = unsafe { Box::from_raw(self.buf) };
-//         }
-//         self.buf = Box::into_raw(new_line);
-//         self.nums = unsafe { Some((*self.buf).split_whitespace()) };
-//     }
-
-//     fn read_token(&mut self) -> &str {
-//         if self.nums.is_none() {
-//             self.get_line();
-//         }
-
-//         loop {
-//             match self.nums.as_mut().unwrap().next() {
-//                 Some(val) => {
-//                     break val;
-//                 }
-//                 None => {
-//                     self.get_line();
-//                 }
-//             }
-//         }
-//     }
-
-//     fn read_int<T: FromStr>(&mut self) -> T
-//     where
-//         <T as FromStr>::Err: Debug,
-//     {
-//         self.read_token().parse().unwrap()
-//     }
-// }
-
-// impl Drop for Reader<'_> {
-//     fn drop(&mut self) {
-//         if !self.buf.is_null() {
-//             let _ = unsafe { Box::from_raw(self.buf) };
-//         }
-//     }
-// }
-
-// unsafe impl Send for Reader<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use board_game::consts::{MAX_BOARD_SIZE, MIN_BOARD_SIZE};
+    use ratatui::backend::TestBackend;
+
+    use super::*;
+
+    // a terminal just big enough for the game screen still fits the board,
+    // and one row less is too small
+    #[test]
+    fn the_too_small_threshold_matches_the_board() {
+        for rows in MIN_BOARD_SIZE..=MAX_BOARD_SIZE {
+            for cols in MIN_BOARD_SIZE..=MAX_BOARD_SIZE {
+                let config = BoardConfig::new(rows, cols, MIN_BOARD_SIZE);
+                let screen = CurrentScreen::GameOn(GameOnScreen::new(config, 0));
+                let (width, height) = TuiGameExecutor::<TestBackend>::min_size(&screen);
+                assert!(width >= MENU_WIDTH_MIN);
+                let board_area = |height: u16| Rect::new(0, 0, width, height - 3);
+                assert!(
+                    BoardStyle::fitting(config, board_area(height)).is_some(),
+                    "{config} does not fit in {width}x{height}"
+                );
+                assert_eq!(BoardStyle::fitting(config, board_area(height - 1)), None);
+            }
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use std::{fs, io, path::Path};
+
+use tracing_appender::{
+    non_blocking::{NonBlocking, WorkerGuard},
+    rolling::{InitError, RollingFileAppender, Rotation},
+};
+use tracing_subscriber::{
+    EnvFilter, filter::ParseError, fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _,
+};
+
+use crate::config::{Config, LogFormat};
+
+// the daily files are named game.log.YYYY-MM-DD
+const LOG_PREFIX: &str = "game.log";
+
+#[derive(Debug, thiserror::Error)]
+pub enum LogError {
+    #[error("Io error: {0:?}")]
+    IoError(#[from] io::Error),
+    #[error("Cannot open the log file: {0}")]
+    InitError(#[from] InitError),
+    #[error("Invalid log level: {0}")]
+    ParseError(#[from] ParseError),
+}
+
+// sends the logs to today's file in `log_dir`, in the configured format;
+// the logs are flushed when the guard is dropped
+pub fn init(log_dir: &Path, config: &Config) -> Result<WorkerGuard, LogError> {
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .max_log_files(config.log_files)
+        .build(log_dir)?;
+    // the appender only prunes when it rotates, which a short session never sees
+    prune(log_dir, config.log_files)?;
+    let (writer, guard) = NonBlocking::new(appender);
+    let text = (config.log_format == LogFormat::Text).then(|| {
+        fmt::layer()
+            .with_writer(writer.clone())
+            .with_ansi(false)
+            .with_file(true)
+            .with_line_number(true)
+            .with_level(true)
+    });
+    let json = (config.log_format == LogFormat::Json).then(|| {
+        fmt::layer()
+            .json()
+            .with_writer(writer)
+            .with_file(true)
+            .with_line_number(true)
+            .with_current_span(true)
+            .with_span_list(true)
+    });
+    tracing_subscriber::registry()
+        .with(text)
+        .with(json)
+        .with(EnvFilter::from_default_env().add_directive(config.log_level.parse()?))
+        .init();
+    Ok(guard)
+}
+
+// deletes all but the newest `keep` log files, today's included
+fn prune(log_dir: &Path, keep: usize) -> io::Result<()> {
+    let mut files: Vec<_> = fs::read_dir(log_dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with(LOG_PREFIX))
+        })
+        .collect();
+    // the date in the name sorts them oldest first
+    files.sort();
+    for path in &files[..files.len().saturating_sub(keep)] {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
@@ -1,14 +1,35 @@
-use std::{io::stdout, sync::Arc};
+#[cfg(feature = "metrics")]
+use std::net::{IpAddr, Ipv4Addr};
+use std::{
+    io::stdout,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::anyhow;
 use board_game::{
+    ai::{Ai, AiLevel},
+    analysis::Analyzer,
+    board::BoardConfig,
+    consts::{MAX_BOARD_SIZE, MIN_BOARD_SIZE},
     game_executor::{GameExecutor, GameExecutorError},
     game_manager::{self, GameManagerError},
+    metrics,
+    record::GameRecord,
+    stats::StatsStore,
+    tablebase::Tablebase,
+    transport::{self, TransportError},
+    wire::Encoding,
+};
+use clap::{
+    Args, Parser, Subcommand, ValueEnum,
+    builder::{PossibleValuesParser, RangedU64ValueParser},
 };
 
 use ratatui::{
     Terminal,
     crossterm::{
+        event::{DisableMouseCapture, EnableMouseCapture},
         execute,
         terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
     },
@@ -16,45 +37,255 @@ use ratatui::{
 };
 use tokio::{
     join,
+    net::{TcpListener, TcpStream},
     sync::{Mutex, mpsc},
-    task::JoinHandle,
+    task::{JoinHandle, JoinSet},
+};
+use tokio_util::sync::CancellationToken;
+use tui_game::{
+    Launch, MARKS, TuiGameExecutor,
+    config::{Config, LOG_LEVELS, LogFormat},
+    logging,
 };
-use tracing_appender::non_blocking::NonBlocking;
-use tracing_subscriber::{EnvFilter, fmt, layer::SubscriberExt as _, util::SubscriberInitExt as _};
-use tui_game::TuiGameExecutor;
+
+// flags override the values from the config file for this run only
+#[derive(Parser)]
+#[command(about = "Tic-tac-toe in the terminal")]
+struct Cli {
+    /// Config file to load and save settings
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+    /// Directory for the daily log files
+    #[arg(long, global = true, default_value = "logs")]
+    log_dir: PathBuf,
+    /// Log level, overriding the config file
+    #[arg(long, global = true, value_parser = PossibleValuesParser::new(LOG_LEVELS))]
+    log_level: Option<String>,
+    /// Log format, overriding the config file
+    #[arg(long, global = true, value_enum)]
+    log_format: Option<LogFormat>,
+    /// Directory of solved tablebases, overriding the config file
+    #[arg(long, global = true, value_name = "DIR")]
+    tablebase_dir: Option<PathBuf>,
+    /// Serve Prometheus metrics at http://ADDR:PORT/metrics
+    #[cfg(feature = "metrics")]
+    #[arg(long, global = true, value_name = "PORT")]
+    metrics_port: Option<u16>,
+    /// Address to serve metrics on; 0.0.0.0 opens them to other machines
+    #[cfg(feature = "metrics")]
+    #[arg(long, global = true, value_name = "ADDR", default_value_t = IpAddr::V4(Ipv4Addr::LOCALHOST))]
+    metrics_addr: IpAddr,
+    /// Opens the main menu when left out
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Start a game right away, skipping the menu
+    Play(PlayArgs),
+    /// Step through a saved game
+    Replay { file: PathBuf },
+    /// Print a move-by-move review of a saved game
+    Analyze { file: PathBuf },
+    /// Host games for clients that connect over TCP
+    Serve {
+        #[arg(long, default_value_t = 7878)]
+        port: u16,
+        /// Encoding offered to clients
+        #[arg(long, value_enum, default_value_t = Wire::Binary)]
+        wire: Wire,
+    },
+    /// Play with the rules run by a game server
+    Connect {
+        addr: String,
+        /// Encoding offered to the server
+        #[arg(long, value_enum, default_value_t = Wire::Binary)]
+        wire: Wire,
+    },
+}
+
+#[derive(Args)]
+struct PlayArgs {
+    /// Board preset, adjusted by --rows, --cols and --k
+    #[arg(long, value_enum)]
+    variant: Option<Variant>,
+    /// Board rows
+    #[arg(long, value_parser = board_size())]
+    rows: Option<usize>,
+    /// Board columns
+    #[arg(long, value_parser = board_size())]
+    cols: Option<usize>,
+    /// Marks in a row needed to win
+    #[arg(long, value_parser = board_size())]
+    k: Option<usize>,
+    /// Play O against the computer: easy, medium or hard
+    #[arg(long, value_name = "LEVEL")]
+    vs_ai: Option<AiLevel>,
+    /// Save every finished game to this file for replay and analyze
+    #[arg(long, value_name = "FILE")]
+    record: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Variant {
+    /// 3x3, three in a row
+    #[value(name = "3x3")]
+    Classic,
+    /// 4x4, four in a row
+    #[value(name = "4x4")]
+    Four,
+    /// 5x5, four in a row
+    #[value(name = "5x5")]
+    Five,
+    /// 7x7, five in a row
+    #[value(name = "7x7")]
+    Seven,
+}
+
+// JSON is used unless both sides offer binary
+#[derive(Clone, Copy, ValueEnum)]
+enum Wire {
+    /// One JSON object per line, for debugging
+    Json,
+    /// Compact length-prefixed frames
+    Binary,
+}
+
+impl From<Wire> for Encoding {
+    fn from(wire: Wire) -> Self {
+        match wire {
+            Wire::Json => Encoding::Json,
+            Wire::Binary => Encoding::Binary,
+        }
+    }
+}
+
+impl Variant {
+    fn board_config(self) -> BoardConfig {
+        match self {
+            Variant::Classic => BoardConfig::new(3, 3, 3),
+            Variant::Four => BoardConfig::new(4, 4, 4),
+            Variant::Five => BoardConfig::new(5, 5, 4),
+            Variant::Seven => BoardConfig::new(7, 7, 5),
+        }
+    }
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    let file_appender = tracing_appender::rolling::daily("./logs", "game.log");
-    let (non_blocking, _guard) = NonBlocking::new(file_appender);
-
-    tracing_subscriber::registry()
-        .with(
-            fmt::layer()
-                .with_writer(non_blocking)
-                .with_file(true)
-                .with_line_number(true)
-                .with_level(true),
-        )
-        .with(EnvFilter::from_default_env().add_directive("trace".parse().unwrap()))
-        .init();
+    let cli = Cli::parse();
+    let config_path = cli.config.clone().unwrap_or_else(Config::path);
+    let config = apply_args(Config::load(&config_path)?, &cli)?;
+
+    let _guard = logging::init(&cli.log_dir, &config)?;
+    #[cfg(feature = "metrics")]
+    if let Some(port) = cli.metrics_port {
+        let listener = TcpListener::bind((cli.metrics_addr, port)).await?;
+        tracing::info!("serving metrics on {}", listener.local_addr()?);
+        tokio::spawn(tui_game::metrics::serve(listener));
+    }
+
+    let tablebase_dir = config.tablebase_dir();
+    let mut executor_options = ExecutorOptions {
+        analyzer: load_analyzer(&tablebase_dir, config.board),
+        launch: Launch::Menu,
+        ai: None,
+        record_path: None,
+        remote: None,
+    };
+    match cli.command {
+        None => {}
+        Some(Command::Play(play)) => {
+            executor_options.launch = Launch::Play;
+            executor_options.ai = play.vs_ai.map(Ai::new);
+            executor_options.record_path = play.record;
+        }
+        Some(Command::Replay { file }) => {
+            let record = GameRecord::load(&file)?;
+            // reject a broken file before the terminal is taken over
+            record.positions()?;
+            executor_options.analyzer = load_analyzer(&tablebase_dir, record.config);
+            executor_options.launch = Launch::Replay(record);
+        }
+        Some(Command::Analyze { file }) => return analyze(&file, &tablebase_dir),
+        Some(Command::Serve { port, wire }) => return serve(port, wire.into()).await,
+        Some(Command::Connect { addr, wire }) => {
+            executor_options.remote = Some((TcpStream::connect(&addr).await?, wire.into()));
+        }
+    }
 
     color_eyre::install().map_err(|err| anyhow!("install error: {err}"))?;
     let mut stdout = std::io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
+    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
     enable_raw_mode()?;
     set_panic_hook();
     let backend = CrosstermBackend::new(stdout);
     let terminal = Arc::new(Mutex::new(ratatui::Terminal::new(backend)?));
-    let result = run(terminal.clone()).await;
-    // ratatui::restore();
+    let cancel_token = CancellationToken::new();
+    cancel_on_signal(cancel_token.clone());
+    let result = run(
+        terminal.clone(),
+        config,
+        config_path,
+        executor_options,
+        cancel_token,
+    )
+    .await;
     if let Err(err) = &result {
         tracing::error!("{err}");
     }
     let mut terminal = terminal.lock().await;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
+    execute!(
+        terminal.backend_mut(),
+        LeaveAlternateScreen,
+        DisableMouseCapture
+    )?;
     disable_raw_mode()?;
-    result
+    if let Err(err) = result {
+        eprintln!("{}", fatal_report(&err, &cli.log_dir));
+        // exit skips destructors, so the log is flushed first
+        drop(_guard);
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+// what the player reads once the terminal is back to normal; the errors
+// name their causes themselves
+fn fatal_report(err: &anyhow::Error, log_dir: &Path) -> String {
+    format!(
+        "The game stopped because of an error: {err}\nThe log in {} has the details.",
+        log_dir.display()
+    )
+}
+
+// resolves on Ctrl-C or, on Unix, SIGTERM
+async fn shutdown_signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        let mut terminate =
+            tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())?;
+        tokio::select! {
+            result = tokio::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+    #[cfg(not(unix))]
+    tokio::signal::ctrl_c().await
+}
+
+// a signal starts the same quit handshake as the Quit key
+fn cancel_on_signal(cancel_token: CancellationToken) {
+    tokio::spawn(async move {
+        match shutdown_signal().await {
+            Ok(()) => {
+                tracing::info!("shutdown requested");
+                cancel_token.cancel();
+            }
+            Err(err) => tracing::error!("cannot listen for signals: {err}"),
+        }
+    });
 }
 
 fn set_panic_hook() {
@@ -67,39 +298,245 @@ fn set_panic_hook() {
 
 /// Restore the terminal to its original state
 pub fn restore() -> anyhow::Result<()> {
-    execute!(stdout(), LeaveAlternateScreen)?;
+    execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
     disable_raw_mode()?;
     Ok(())
 }
 
-async fn run<B: Backend + std::marker::Send + std::marker::Sync + 'static>(
-    terminal: Arc<Mutex<Terminal<B>>>,
+// sizes a board can be given on the command line with
+fn board_size() -> RangedU64ValueParser<usize> {
+    RangedU64ValueParser::new().range(MIN_BOARD_SIZE as u64..=MAX_BOARD_SIZE as u64)
+}
+
+// values from the config file are clamped into range, but ones given on the
+// command line are refused
+fn apply_args(config: Config, cli: &Cli) -> anyhow::Result<Config> {
+    let mut config = config.validated();
+    if let Some(Command::Play(play)) = &cli.command {
+        if let Some(variant) = play.variant {
+            config.board = variant.board_config();
+        }
+        if let Some(rows) = play.rows {
+            config.board.rows = rows;
+        }
+        if let Some(cols) = play.cols {
+            config.board.cols = cols;
+        }
+        if let Some(k) = play.k {
+            config.board.k = k;
+        }
+        let board = config.board;
+        if !board.is_supported() {
+            return Err(anyhow!(
+                "a line of {} does not fit on a {}x{} board",
+                board.k,
+                board.rows,
+                board.cols
+            ));
+        }
+    }
+    if let Some(log_level) = &cli.log_level {
+        config.log_level = log_level.clone();
+    }
+    if let Some(log_format) = cli.log_format {
+        config.log_format = log_format;
+    }
+    if let Some(tablebase_dir) = &cli.tablebase_dir {
+        config.tablebase_dir = Some(tablebase_dir.clone());
+    }
+    Ok(config.validated())
+}
+
+fn stats_path() -> std::path::PathBuf {
+    dirs::data_dir()
+        .unwrap_or_else(|| ".".into())
+        .join("tictactoe")
+        .join("stats.jsonl")
+}
+
+// uses a pregenerated tablebase when there is one, and searches otherwise
+fn load_analyzer(tablebase_dir: &Path, config: BoardConfig) -> Analyzer {
+    let path = tablebase_dir.join(Tablebase::file_name(config));
+    match Tablebase::load(&path, config) {
+        Ok(tablebase) => {
+            tracing::info!("loaded tablebase {}", path.display());
+            Analyzer::new().set_tablebase(Arc::new(tablebase))
+        }
+        Err(err) => {
+            tracing::debug!("no tablebase at {}: {err}", path.display());
+            Analyzer::new()
+        }
+    }
+}
+
+fn analyze(file: &Path, tablebase_dir: &Path) -> anyhow::Result<()> {
+    let record = GameRecord::load(file)?;
+    let positions = record.positions()?;
+    let mut analyzer = load_analyzer(tablebase_dir, record.config);
+    let reviews = analyzer.review(record.config, record.first_player, &record.moves)?;
+    println!(
+        "{}, {} first, {} moves",
+        record.config,
+        MARKS[record.first_player],
+        record.moves.len()
+    );
+    for (number, review) in reviews.iter().enumerate() {
+        let (row, col) = positions[0].position(review.index);
+        println!(
+            "{:>2}. {} ({}, {})  {} -> {}{}",
+            number + 1,
+            MARKS[review.player],
+            row,
+            col,
+            review.before,
+            review.after,
+            if review.is_blunder {
+                "  ?? blunder"
+            } else {
+                ""
+            }
+        );
+    }
+    let last = positions
+        .last()
+        .expect("positions start with the empty board");
+    match last.winner() {
+        Some(winner) => println!("Result: {} wins", MARKS[winner]),
+        None if last.is_full() => println!("Result: draw"),
+        None => println!("Result: unfinished"),
+    }
+    Ok(())
+}
+
+// every client gets its own game manager
+async fn serve(port: u16, encoding: Encoding) -> anyhow::Result<()> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).await?;
+    println!("Listening on {}", listener.local_addr()?);
+    let cancel_token = CancellationToken::new();
+    cancel_on_signal(cancel_token.clone());
+    let mut clients = JoinSet::new();
+    loop {
+        let (stream, addr) = tokio::select! {
+            accepted = listener.accept() => accepted?,
+            Some(_) = clients.join_next() => continue,
+            _ = cancel_token.cancelled() => break,
+        };
+        tracing::info!("client {addr} connected");
+        // a client that fails ends only its own game
+        let cancel_token = cancel_token.child_token();
+        clients.spawn(async move {
+            metrics::CONNECTIONS.add(1);
+            if let Err(err) = serve_client(stream, encoding, cancel_token).await {
+                tracing::error!("client {addr}: {err}");
+            }
+            metrics::CONNECTIONS.add(-1);
+            tracing::info!("client {addr} disconnected");
+        });
+    }
+    println!("Shutting down");
+    // every game quits through the handshake or is dropped at the deadline
+    while clients.join_next().await.is_some() {}
+    Ok(())
+}
+
+async fn serve_client(
+    stream: TcpStream,
+    encoding: Encoding,
+    cancel_token: CancellationToken,
 ) -> anyhow::Result<()> {
     let (manager_to_executor_tx, manager_to_executor_rx) = mpsc::channel(32);
     let (executor_to_manager_tx, executor_to_manager_rx) = mpsc::channel(32);
-
     let game_manager = Arc::new(
         game_manager::GameManager::new()
             .set_rx(executor_to_manager_rx)
-            .set_tx(manager_to_executor_tx),
+            .set_tx(manager_to_executor_tx)
+            .set_cancel_token(cancel_token),
     );
-
-    let game_executor = Arc::new(
-        TuiGameExecutor::new(terminal)
-            .set_rx(manager_to_executor_rx)
-            .set_tx(executor_to_manager_tx),
+    let (manager_result, bridge_result) = join!(
+        game_manager.start(),
+        transport::bridge(
+            stream,
+            encoding,
+            executor_to_manager_tx,
+            manager_to_executor_rx
+        )
     );
+    manager_result?;
+    bridge_result?;
+    Ok(())
+}
+
+struct ExecutorOptions {
+    analyzer: Analyzer,
+    launch: Launch,
+    ai: Option<Ai>,
+    record_path: Option<PathBuf>,
+    // a game server to use instead of a local game manager, and the encoding
+    // to offer it
+    remote: Option<(TcpStream, Encoding)>,
+}
+
+async fn run<B: Backend + std::marker::Send + std::marker::Sync + 'static>(
+    terminal: Arc<Mutex<Terminal<B>>>,
+    config: Config,
+    config_path: PathBuf,
+    options: ExecutorOptions,
+    cancel_token: CancellationToken,
+) -> anyhow::Result<()> {
+    let (manager_to_executor_tx, manager_to_executor_rx) = mpsc::channel(32);
+    let (executor_to_manager_tx, executor_to_manager_rx) = mpsc::channel(32);
+
+    let mut game_executor = TuiGameExecutor::new(terminal)
+        .set_rx(manager_to_executor_rx)
+        .set_tx(executor_to_manager_tx)
+        .set_analyzer(options.analyzer)
+        .set_stats_store(StatsStore::new(stats_path()))
+        .set_config(config)
+        .set_config_path(config_path)
+        .set_launch(options.launch)
+        .set_cancel_token(cancel_token.clone());
+    if let Some(ai) = options.ai {
+        game_executor = game_executor.set_ai(ai);
+    }
+    if let Some(record_path) = options.record_path {
+        game_executor = game_executor.set_record_path(record_path);
+    }
+    let game_executor = Arc::new(game_executor);
 
     let game_executor_task: JoinHandle<Result<(), GameExecutorError>> = tokio::spawn(async move {
         game_executor.run().await?;
         Ok(())
     });
 
-    let game_manager_task: JoinHandle<Result<(), GameManagerError>> = tokio::spawn(async move {
-        game_manager.start().await?;
-        Ok(())
-    });
-
-    join!(game_executor_task, game_manager_task).0??;
+    match options.remote {
+        None => {
+            let game_manager = Arc::new(
+                game_manager::GameManager::new()
+                    .set_rx(executor_to_manager_rx)
+                    .set_tx(manager_to_executor_tx)
+                    .set_cancel_token(cancel_token),
+            );
+            let game_manager_task: JoinHandle<Result<(), GameManagerError>> =
+                tokio::spawn(async move {
+                    game_manager.start().await?;
+                    Ok(())
+                });
+            let (executor_result, manager_result) = join!(game_executor_task, game_manager_task);
+            executor_result??;
+            manager_result??;
+        }
+        Some((stream, encoding)) => {
+            let bridge_task: JoinHandle<Result<(), TransportError>> =
+                tokio::spawn(transport::bridge(
+                    stream,
+                    encoding,
+                    manager_to_executor_tx,
+                    executor_to_manager_rx,
+                ));
+            let (executor_result, bridge_result) = join!(game_executor_task, bridge_task);
+            executor_result??;
+            bridge_result??;
+        }
+    }
     Ok(())
 }
@@ -0,0 +1,227 @@
+use std::collections::BTreeMap;
+
+use board_game::printer::color::Color as HexColor;
+use num_enum::TryFromPrimitive;
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, TryFromPrimitive,
+)]
+#[serde(rename_all = "snake_case")]
+#[repr(usize)]
+pub enum ThemeElement {
+    Background,
+    Text,
+    Border,
+    Title,
+    // text of the selected menu entry
+    Selection,
+    // background of the selected menu entry
+    Highlight,
+    Cursor,
+    Hint,
+    WinningLine,
+    Help,
+    Error,
+    PlayerX,
+    PlayerO,
+}
+
+pub const THEME_ELEMENT_NUMS: usize = 13;
+
+impl ThemeElement {
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemeElement::Background => "Background",
+            ThemeElement::Text => "Text",
+            ThemeElement::Border => "Borders",
+            ThemeElement::Title => "Title",
+            ThemeElement::Selection => "Selected text",
+            ThemeElement::Highlight => "Selected background",
+            ThemeElement::Cursor => "Board cursor",
+            ThemeElement::Hint => "Hinted cells",
+            ThemeElement::WinningLine => "Winning line",
+            ThemeElement::Help => "Help text",
+            ThemeElement::Error => "Errors",
+            ThemeElement::PlayerX => "X marks",
+            ThemeElement::PlayerO => "O marks",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize, TryFromPrimitive)]
+#[serde(rename_all = "snake_case")]
+#[repr(usize)]
+pub enum ThemePreset {
+    #[default]
+    Dark,
+    Light,
+    HighContrast,
+    // the Okabe-Ito palette, told apart with any kind of color blindness
+    Colorblind,
+}
+
+pub const THEME_PRESET_NUMS: usize = 4;
+
+impl ThemePreset {
+    pub fn label(self) -> &'static str {
+        match self {
+            ThemePreset::Dark => "dark",
+            ThemePreset::Light => "light",
+            ThemePreset::HighContrast => "high contrast",
+            ThemePreset::Colorblind => "colorblind",
+        }
+    }
+
+    // in ThemeElement order
+    fn hex_colors(self) -> [&'static str; THEME_ELEMENT_NUMS] {
+        match self {
+            ThemePreset::Dark => [
+                "#000000", "#d0d0d0", "#d0d0d0", "#00ff00", "#ff5555", "#808080", "#808080",
+                "#00ff00", "#ff5555", "#808080", "#ff5555", "#00ffff", "#ffff00",
+            ],
+            ThemePreset::Light => [
+                "#fafafa", "#202020", "#606060", "#00875f", "#ffffff", "#005fd7", "#c6c6c6",
+                "#afd7af", "#d70000", "#6c6c6c", "#d70000", "#005fd7", "#af5f00",
+            ],
+            ThemePreset::HighContrast => [
+                "#000000", "#ffffff", "#ffffff", "#ffff00", "#000000", "#ffff00", "#0000ff",
+                "#008000", "#ff00ff", "#ffffff", "#ff0000", "#00ffff", "#ffff00",
+            ],
+            ThemePreset::Colorblind => [
+                "#000000", "#ffffff", "#999999", "#009e73", "#000000", "#56b4e9", "#0072b2",
+                "#009e73", "#f0e442", "#999999", "#d55e00", "#56b4e9", "#e69f00",
+            ],
+        }
+    }
+}
+
+// a preset plus the colors picked by hand, as stored in the config file
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ThemeSettings {
+    pub preset: ThemePreset,
+    // hex strings such as "#00ff00" that replace the preset's color
+    pub colors: BTreeMap<ThemeElement, String>,
+}
+
+impl ThemeSettings {
+    pub fn hex(&self, element: ThemeElement) -> String {
+        match self.colors.get(&element) {
+            Some(hex) => hex.clone(),
+            None => self.preset.hex_colors()[element as usize].to_string(),
+        }
+    }
+
+    pub fn theme(&self) -> Theme {
+        let preset = self.preset.hex_colors();
+        let color = |element: ThemeElement| {
+            // a bad hand-edited color falls back to the preset's
+            parse_color(&self.hex(element))
+                .or_else(|| parse_color(preset[element as usize]))
+                .unwrap_or(Color::Reset)
+        };
+        Theme {
+            background: color(ThemeElement::Background),
+            text: color(ThemeElement::Text),
+            border: color(ThemeElement::Border),
+            title: color(ThemeElement::Title),
+            selection: color(ThemeElement::Selection),
+            highlight: color(ThemeElement::Highlight),
+            cursor: color(ThemeElement::Cursor),
+            hint: color(ThemeElement::Hint),
+            winning_line: color(ThemeElement::WinningLine),
+            help: color(ThemeElement::Help),
+            error: color(ThemeElement::Error),
+            players: [color(ThemeElement::PlayerX), color(ThemeElement::PlayerO)],
+        }
+    }
+}
+
+// the colors every screen is drawn with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub background: Color,
+    pub text: Color,
+    pub border: Color,
+    pub title: Color,
+    pub selection: Color,
+    pub highlight: Color,
+    pub cursor: Color,
+    pub hint: Color,
+    pub winning_line: Color,
+    pub help: Color,
+    pub error: Color,
+    pub players: [Color; 2],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        ThemeSettings::default().theme()
+    }
+}
+
+pub fn parse_color(hex: &str) -> Option<Color> {
+    let color = HexColor::new_from_hex_str(hex).ok()?;
+    Some(Color::Rgb(color.red, color.green, color.blue))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn presets() -> impl Iterator<Item = ThemePreset> {
+        (0..THEME_PRESET_NUMS).map(|index| ThemePreset::try_from(index).unwrap())
+    }
+
+    #[test]
+    fn every_preset_color_parses() {
+        for preset in presets() {
+            for hex in preset.hex_colors() {
+                assert!(parse_color(hex).is_some(), "{preset:?} has {hex}");
+            }
+        }
+    }
+
+    #[test]
+    fn presets_are_looked_up_by_element() {
+        let dark = ThemeSettings::default().theme();
+        assert_eq!(dark.background, Color::Rgb(0, 0, 0));
+        assert_eq!(
+            dark.players,
+            [Color::Rgb(0, 255, 255), Color::Rgb(255, 255, 0)]
+        );
+        let light = ThemeSettings {
+            preset: ThemePreset::Light,
+            ..ThemeSettings::default()
+        };
+        assert_eq!(light.hex(ThemeElement::Background), "#fafafa");
+        assert_eq!(light.theme().error, Color::Rgb(0xd7, 0, 0));
+        // the marks stay apart in every preset
+        for preset in presets() {
+            let settings = ThemeSettings {
+                preset,
+                ..ThemeSettings::default()
+            };
+            let theme = settings.theme();
+            assert_ne!(theme.players[0], theme.players[1], "{preset:?}");
+            assert_ne!(theme.text, theme.background, "{preset:?}");
+        }
+    }
+
+    #[test]
+    fn picked_colors_replace_the_preset_and_bad_ones_fall_back() {
+        let settings: ThemeSettings = toml::from_str(
+            "preset = \"high_contrast\"\n[colors]\ntitle = \"#123456\"\nerror = \"crimson\"\n",
+        )
+        .unwrap();
+        assert_eq!(settings.preset, ThemePreset::HighContrast);
+        let theme = settings.theme();
+        assert_eq!(theme.title, Color::Rgb(0x12, 0x34, 0x56));
+        // names aren't hex, so the preset's red stays
+        assert_eq!(theme.error, Color::Rgb(255, 0, 0));
+        assert_eq!(settings.hex(ThemeElement::Error), "crimson");
+        assert_eq!(theme.text, Color::Rgb(255, 255, 255));
+    }
+}
@@ -1,3 +1,7 @@
 pub mod state;
 
-pub mod screen;
\ No newline at end of file
+pub mod screen;
+
+pub mod board_style;
+
+pub mod animation;
@@ -0,0 +1,44 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{buffer::Buffer, style::Color};
+
+// the redraw rate while something is moving
+pub const FRAME_INTERVAL: Duration = Duration::from_millis(33);
+
+pub const MARK_DURATION: Duration = Duration::from_millis(300);
+pub const FLASH_DURATION: Duration = Duration::from_millis(1600);
+const FLASH_PERIOD: Duration = Duration::from_millis(200);
+pub const FADE_DURATION: Duration = Duration::from_millis(250);
+
+// how far an animation started at `start` has run, from 0.0 up to 1.0, or
+// None once it is over
+pub fn progress(start: Instant, duration: Duration) -> Option<f32> {
+    let elapsed = start.elapsed();
+    (elapsed < duration).then(|| elapsed.as_secs_f32() / duration.as_secs_f32())
+}
+
+// whether a line that started flashing at `start` is lit right now; it
+// stays lit once the flashing is over
+pub fn flash_on(start: Instant) -> bool {
+    let elapsed = start.elapsed();
+    elapsed >= FLASH_DURATION || (elapsed.as_millis() / FLASH_PERIOD.as_millis()) % 2 == 0
+}
+
+// blends every drawn color from the background towards its final value;
+// only RGB colors can be blended, so the rest are left as they are
+pub fn fade(buffer: &mut Buffer, background: Color, progress: f32) {
+    let Color::Rgb(red, green, blue) = background else {
+        return;
+    };
+    let blend = |color: Color| match color {
+        Color::Rgb(r, g, b) => {
+            let mix = |from: u8, to: u8| (from as f32 + (to as f32 - from as f32) * progress) as u8;
+            Color::Rgb(mix(red, r), mix(green, g), mix(blue, b))
+        }
+        color => color,
+    };
+    for cell in buffer.content.iter_mut() {
+        cell.fg = blend(cell.fg);
+        cell.bg = blend(cell.bg);
+    }
+}
@@ -0,0 +1,101 @@
+use board_game::board::BoardConfig;
+use ratatui::layout::Rect;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BoardStyle {
+    // one character per cell, spaced apart
+    Compact,
+    // cells three columns wide inside a grid of box lines
+    #[default]
+    Boxed,
+}
+
+impl BoardStyle {
+    // the larger style when it fits in the area, None when neither does
+    pub fn fitting(config: BoardConfig, area: Rect) -> Option<Self> {
+        [BoardStyle::Boxed, BoardStyle::Compact]
+            .into_iter()
+            .find(|style| {
+                let (width, height) = style.size(config);
+                width <= area.width && height <= area.height
+            })
+    }
+
+    // width and height in terminal cells
+    pub fn size(self, config: BoardConfig) -> (u16, u16) {
+        let (rows, cols) = (config.rows as u16, config.cols as u16);
+        match self {
+            BoardStyle::Compact => (cols * 2 - 1, rows),
+            BoardStyle::Boxed => (cols * 4 + 1, rows * 2 + 1),
+        }
+    }
+
+    // the cell under a screen position, for a board drawn at the top of the
+    // area and centered the way Paragraph centers a line
+    pub fn cell_at(
+        self,
+        config: BoardConfig,
+        area: Rect,
+        column: u16,
+        row: u16,
+    ) -> Option<(usize, usize)> {
+        let (width, height) = self.size(config);
+        let left = area.x + (area.width / 2).saturating_sub(width / 2);
+        let x = column.checked_sub(left)?;
+        let y = row.checked_sub(area.y)?;
+        if x >= width || y >= height {
+            return None;
+        }
+        match self {
+            BoardStyle::Compact => (x % 2 == 0).then_some((y as usize, (x / 2) as usize)),
+            // the box lines sit on every fourth column and every other row
+            BoardStyle::Boxed => {
+                (x % 4 != 0 && y % 2 == 1).then_some(((y / 2) as usize, (x / 4) as usize))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: BoardConfig = BoardConfig {
+        rows: 3,
+        cols: 4,
+        k: 3,
+    };
+
+    #[test]
+    fn the_boxed_style_is_chosen_while_it_fits() {
+        assert_eq!(BoardStyle::Boxed.size(CONFIG), (17, 7));
+        assert_eq!(BoardStyle::Compact.size(CONFIG), (7, 3));
+        let fitting = |width, height| BoardStyle::fitting(CONFIG, Rect::new(0, 0, width, height));
+        assert_eq!(fitting(17, 7), Some(BoardStyle::Boxed));
+        assert_eq!(fitting(16, 7), Some(BoardStyle::Compact));
+        assert_eq!(fitting(17, 6), Some(BoardStyle::Compact));
+        assert_eq!(fitting(7, 3), Some(BoardStyle::Compact));
+        assert_eq!(fitting(6, 3), None);
+        assert_eq!(fitting(7, 2), None);
+    }
+
+    #[test]
+    fn clicks_find_the_cell_under_them() {
+        // a 21 wide area centers the 17 wide board two columns in
+        let area = Rect::new(10, 5, 21, 10);
+        let boxed = |column, row| BoardStyle::Boxed.cell_at(CONFIG, area, column, row);
+        assert_eq!(boxed(13, 6), Some((0, 0)));
+        assert_eq!(boxed(27, 10), Some((2, 3)));
+        // box lines and anything past the board
+        assert_eq!(boxed(12, 6), None);
+        assert_eq!(boxed(13, 7), None);
+        assert_eq!(boxed(29, 10), None);
+        assert_eq!(boxed(13, 12), None);
+
+        let compact = |column, row| BoardStyle::Compact.cell_at(CONFIG, area, column, row);
+        assert_eq!(compact(17, 5), Some((0, 0)));
+        assert_eq!(compact(23, 7), Some((2, 3)));
+        assert_eq!(compact(18, 5), None);
+        assert_eq!(compact(17, 8), None);
+    }
+}
@@ -1,3 +1,22 @@
+use ratatui::layout::{Position, Rect};
+
 pub mod main_screen;
 
 pub mod game_on_screen;
+
+pub mod game_over_screen;
+
+pub mod review_screen;
+
+pub mod statistics_screen;
+
+pub mod settings_screen;
+
+pub mod replay_screen;
+
+// the index of the area that contains a screen position
+fn area_at(areas: &[Rect], column: u16, row: u16) -> Option<usize> {
+    areas
+        .iter()
+        .position(|area| area.contains(Position::new(column, row)))
+}
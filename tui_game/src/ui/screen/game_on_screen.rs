@@ -1,14 +1,183 @@
-#[derive(Clone, Copy)]
-pub struct GameOnScreen {}
+use std::time::Instant;
+
+use board_game::{
+    analysis::{Analyzer, GameValue},
+    bitboard::{BitBoard, BitBoardError},
+    board::{Board, BoardConfig},
+    message::snapshot::{Outcome, StateSnapshot},
+    tile::Tile,
+};
+use ratatui::layout::Rect;
+
+use crate::ui::board_style::BoardStyle;
+
+pub const MARKS: [&str; 2] = ["X", "O"];
+pub const PLAYER_NAMES: [&str; 2] = ["Player 1", "Player 2"];
+
+#[derive(Clone)]
+pub struct GameOnScreen {
+    pub board: BitBoard,
+    pub players: [String; 2],
+    pub started_at: Instant,
+    pub first_player: usize,
+    pub cursor: (usize, usize),
+    // cell indices in the order they were played
+    pub moves: Vec<usize>,
+    // when each move was placed, for the animation
+    pub placed_at: Vec<Instant>,
+    pub hint: Vec<(usize, usize)>,
+    pub hint_value: Option<GameValue>,
+    // the board is too large to search for a hint
+    pub hint_unavailable: bool,
+    // a hint is being searched for
+    pub hint_pending: bool,
+    pub winning_line: Vec<(usize, usize)>,
+    // the player whose move the manager has not answered yet
+    pub pending_move: Option<usize>,
+    // the AI is choosing its move
+    pub ai_thinking: bool,
+    // where and how the board was last drawn, for the mouse
+    pub board_area: Rect,
+    pub board_style: BoardStyle,
+}
 
 impl GameOnScreen {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(config: BoardConfig, first_player: usize) -> Self {
+        let mut board = BitBoard::new(config);
+        board.set_side_to_move(first_player);
+        Self {
+            board,
+            players: PLAYER_NAMES.map(String::from),
+            started_at: Instant::now(),
+            first_player,
+            cursor: (config.rows / 2, config.cols / 2),
+            moves: vec![],
+            placed_at: vec![],
+            hint: vec![],
+            hint_value: None,
+            hint_unavailable: false,
+            hint_pending: false,
+            winning_line: vec![],
+            pending_move: None,
+            ai_thinking: false,
+            board_area: Rect::default(),
+            board_style: BoardStyle::default(),
+        }
+    }
+
+    pub fn move_cursor(&mut self, drow: isize, dcol: isize) {
+        let config = self.board.config();
+        self.cursor.0 = self
+            .cursor
+            .0
+            .saturating_add_signed(drow)
+            .min(config.rows - 1);
+        self.cursor.1 = self
+            .cursor
+            .1
+            .saturating_add_signed(dcol)
+            .min(config.cols - 1);
+    }
+
+    pub fn is_over(&self) -> bool {
+        Analyzer::is_decided(&self.board)
+    }
+
+    // places a mark for the side to move, returning the player
+    pub fn place(&mut self, row: usize, col: usize) -> Option<usize> {
+        let player = self.board.side_to_move();
+        if self.is_over() || self.board.place(row, col).is_err() {
+            return None;
+        }
+        self.moves.push(self.board.index(row, col));
+        self.placed_at.push(Instant::now());
+        self.hint.clear();
+        self.hint_value = None;
+        self.hint_unavailable = false;
+        self.hint_pending = false;
+        self.winning_line = Board::from(&self.board)
+            .winning_line(row, col)
+            .unwrap_or_default();
+        Some(player)
+    }
+
+    pub fn cell_at(&self, column: u16, row: u16) -> Option<(usize, usize)> {
+        self.board_style
+            .cell_at(self.board.config(), self.board_area, column, row)
+    }
+
+    // takes the manager's board when it disagrees with the one on screen and
+    // returns whether it did; a snapshot taken before or after the moves
+    // shown here cannot be compared and is left alone
+    pub fn resync(&mut self, snapshot: &StateSnapshot) -> Result<bool, BitBoardError> {
+        if snapshot.config != self.board.config() || snapshot.move_number != self.moves.len() {
+            return Ok(false);
+        }
+        self.rebuild(snapshot)
+    }
+
+    // takes the manager's board whatever the screen shows, for a snapshot
+    // known to be current; returns whether the board changed
+    pub fn rebuild(&mut self, snapshot: &StateSnapshot) -> Result<bool, BitBoardError> {
+        if snapshot.config != self.board.config() {
+            (self.moves, self.placed_at) = (vec![], vec![]);
+            self.board = BitBoard::new(snapshot.config);
+        }
+        let mut tiles = Board::with_config(snapshot.config);
+        for (row, owners) in snapshot.board.iter().enumerate() {
+            for (col, owner) in owners.iter().enumerate() {
+                tiles.board[row][col] = owner.clone().map(|player| Tile {
+                    owner: Some(player),
+                });
+            }
+        }
+        let mut board = BitBoard::try_from(&tiles)?;
+        board.set_side_to_move(snapshot.current_player.id);
+        let cells = 0..snapshot.config.cells();
+        if board.side_to_move() == self.board.side_to_move()
+            && cells
+                .clone()
+                .all(|index| board.owner(index) == self.board.owner(index))
+        {
+            return Ok(false);
+        }
+        // the order of moves only the manager saw is unknown, so undo can
+        // reach back only through the moves both sides agree on
+        (self.moves, self.placed_at) = self
+            .moves
+            .iter()
+            .zip(&self.placed_at)
+            .filter(|(index, _)| board.owner(**index) == self.board.owner(**index))
+            .map(|(index, placed_at)| (*index, *placed_at))
+            .unzip();
+        self.board = board;
+        self.hint.clear();
+        self.hint_value = None;
+        self.hint_unavailable = false;
+        self.hint_pending = false;
+        self.winning_line = match &snapshot.outcome {
+            Outcome::Won(_, line) => line.clone(),
+            _ => vec![],
+        };
+        Ok(true)
+    }
+
+    // takes back the last move, returning its cell
+    pub fn undo(&mut self) -> Option<(usize, usize)> {
+        let index = self.moves.pop()?;
+        self.placed_at.pop();
+        self.board.undo(index);
+        self.hint.clear();
+        self.hint_value = None;
+        self.hint_unavailable = false;
+        self.hint_pending = false;
+        self.winning_line.clear();
+        Some(self.board.position(index))
     }
 }
 
 impl Default for GameOnScreen {
     fn default() -> Self {
-        Self::new()
+        Self::new(BoardConfig::default(), 0)
     }
 }
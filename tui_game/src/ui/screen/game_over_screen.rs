@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use board_game::{bitboard::BitBoard, record::GameRecord, stats::GameResult};
+use num_enum::TryFromPrimitive;
+use ratatui::layout::Rect;
+
+use super::{area_at, game_on_screen::GameOnScreen};
+
+#[derive(Clone)]
+pub struct GameOverScreen {
+    pub board: BitBoard,
+    pub players: [String; 2],
+    pub duration: Duration,
+    pub first_player: usize,
+    pub moves: Vec<usize>,
+    // None on a draw
+    pub winner: Option<usize>,
+    pub winning_line: Vec<(usize, usize)>,
+    pub menu_select: GameOverMenu,
+    // the board is too large to search for a review
+    pub review_unavailable: bool,
+    // the review is being searched for
+    pub review_pending: bool,
+    // the winning line flashes from here on
+    pub finished_at: Instant,
+    // where each entry was last drawn, for the mouse
+    pub menu_areas: Vec<Rect>,
+}
+
+impl GameOverScreen {
+    pub fn new(game_on_screen: GameOnScreen, winner: Option<usize>) -> Self {
+        Self {
+            duration: game_on_screen.started_at.elapsed(),
+            players: game_on_screen.players,
+            board: game_on_screen.board,
+            first_player: game_on_screen.first_player,
+            moves: game_on_screen.moves,
+            winner,
+            winning_line: game_on_screen.winning_line,
+            menu_select: GameOverMenu::Rematch,
+            review_unavailable: false,
+            review_pending: false,
+            finished_at: Instant::now(),
+            menu_areas: vec![],
+        }
+    }
+
+    pub fn menu_at(&self, column: u16, row: u16) -> Option<GameOverMenu> {
+        area_at(&self.menu_areas, column, row).map(|index| GameOverMenu::try_from(index).unwrap())
+    }
+
+    pub fn result(&self) -> GameResult {
+        GameResult {
+            players: self.players.clone(),
+            variant: self.board.config().to_string(),
+            winner: self.winner,
+            moves: self.moves.len(),
+            duration_secs: self.duration.as_secs_f64(),
+            finished_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+        }
+    }
+
+    pub fn record(&self) -> GameRecord {
+        GameRecord::new(self.board.config(), self.first_player, self.moves.clone())
+    }
+}
+
+#[derive(Clone, Copy, TryFromPrimitive)]
+#[repr(usize)]
+pub enum GameOverMenu {
+    Rematch,
+    Review,
+    MainMenu,
+    Quit,
+}
+
+pub const GAME_OVER_MENU_NUMS: usize = 4;
+
+impl GameOverMenu {
+    pub fn label(self) -> &'static str {
+        match self {
+            GameOverMenu::Rematch => "Rematch",
+            GameOverMenu::Review => "Review",
+            GameOverMenu::MainMenu => "Main Menu",
+            GameOverMenu::Quit => "Quit",
+        }
+    }
+}
@@ -1,16 +1,26 @@
-use std::sync::Arc;
+use num_enum::TryFromPrimitive;
+use ratatui::layout::Rect;
 
-use num_enum::{IntoPrimitive, TryFromPrimitive};
-use tokio::sync::Mutex;
+use super::area_at;
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub struct MainScreen {
     pub menu_select: Option<CurrentSelectMenu>,
+    // where each entry was last drawn, for the mouse
+    pub menu_areas: Vec<Rect>,
 }
 
 impl MainScreen {
     pub fn new() -> Self {
-        Self { menu_select: None }
+        Self {
+            menu_select: None,
+            menu_areas: vec![],
+        }
+    }
+
+    pub fn menu_at(&self, column: u16, row: u16) -> Option<CurrentSelectMenu> {
+        area_at(&self.menu_areas, column, row)
+            .map(|index| CurrentSelectMenu::try_from(index).unwrap())
     }
 }
 
@@ -24,7 +34,20 @@ impl Default for MainScreen {
 #[repr(usize)]
 pub enum CurrentSelectMenu {
     StartGame,
+    Statistics,
+    Settings,
     QuitGame,
 }
 
-pub const SELECT_MENU_NUMS: usize = 2;
+pub const SELECT_MENU_NUMS: usize = 4;
+
+impl CurrentSelectMenu {
+    pub fn label(self) -> &'static str {
+        match self {
+            CurrentSelectMenu::StartGame => "Start Game",
+            CurrentSelectMenu::Statistics => "Statistics",
+            CurrentSelectMenu::Settings => "Settings",
+            CurrentSelectMenu::QuitGame => "Quit Game",
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use board_game::{
+    bitboard::BitBoard,
+    record::{GameRecord, RecordError},
+};
+
+#[derive(Clone)]
+pub struct ReplayScreen {
+    pub record: GameRecord,
+    // positions[n] is the board after the first n moves
+    pub positions: Vec<BitBoard>,
+    pub shown: usize,
+}
+
+impl ReplayScreen {
+    pub fn new(record: GameRecord) -> Result<Self, RecordError> {
+        let positions = record.positions()?;
+        Ok(Self {
+            record,
+            positions,
+            shown: 0,
+        })
+    }
+
+    pub fn board(&self) -> &BitBoard {
+        &self.positions[self.shown]
+    }
+
+    // the cell of the move that led to the shown position
+    pub fn last_move(&self) -> Option<usize> {
+        self.shown
+            .checked_sub(1)
+            .map(|number| self.record.moves[number])
+    }
+
+    pub fn step(&mut self, delta: isize) {
+        self.shown = self
+            .shown
+            .saturating_add_signed(delta)
+            .min(self.record.moves.len());
+    }
+}
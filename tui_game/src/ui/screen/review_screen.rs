@@ -0,0 +1,40 @@
+use board_game::{analysis::MoveReview, bitboard::BitBoard, board::BoardConfig};
+
+#[derive(Clone)]
+pub struct ReviewScreen {
+    pub config: BoardConfig,
+    pub first_player: usize,
+    pub reviews: Vec<MoveReview>,
+    pub selected: usize,
+}
+
+impl ReviewScreen {
+    pub fn new(config: BoardConfig, first_player: usize, reviews: Vec<MoveReview>) -> Self {
+        Self {
+            config,
+            first_player,
+            reviews,
+            selected: 0,
+        }
+    }
+
+    // the position right after the selected move
+    pub fn selected_board(&self) -> BitBoard {
+        let mut board = BitBoard::new(self.config);
+        board.set_side_to_move(self.first_player);
+        for review in self.reviews.iter().take(self.selected + 1) {
+            board.play(review.index);
+        }
+        board
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.reviews.len() {
+            self.selected += 1;
+        }
+    }
+}
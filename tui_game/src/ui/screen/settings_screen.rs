@@ -0,0 +1,228 @@
+use board_game::{
+    consts::{MAX_BOARD_SIZE, MIN_BOARD_SIZE},
+    printer::color::Color as HexColor,
+};
+
+use crate::{
+    config::{Config, LOG_LEVELS, LogFormat},
+    keymap::{ACTION_NUMS, Action, KEYMAP_PRESET_NUMS, KeymapPreset},
+    theme::{THEME_ELEMENT_NUMS, THEME_PRESET_NUMS, ThemeElement, ThemePreset},
+};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SettingsItem {
+    Rows,
+    Cols,
+    K,
+    LogLevel,
+    LogFormat,
+    Animations,
+    ThemePreset,
+    // one row per theme element, after the theme preset
+    Color(ThemeElement),
+    KeymapPreset,
+    // one row per action, after the keymap preset
+    Key(Action),
+}
+
+const GENERAL_ITEMS: [SettingsItem; 7] = [
+    SettingsItem::Rows,
+    SettingsItem::Cols,
+    SettingsItem::K,
+    SettingsItem::LogLevel,
+    SettingsItem::LogFormat,
+    SettingsItem::Animations,
+    SettingsItem::ThemePreset,
+];
+
+const KEYMAP_PRESET_INDEX: usize = GENERAL_ITEMS.len() + THEME_ELEMENT_NUMS;
+
+pub const SETTINGS_ITEM_NUMS: usize = KEYMAP_PRESET_INDEX + 1 + ACTION_NUMS;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum SettingsKind {
+    // changed with left and right
+    Number,
+    Choice,
+    // typed in as text
+    Color,
+    // captured from the next key press
+    Key,
+}
+
+impl SettingsItem {
+    pub fn from_index(index: usize) -> Self {
+        if let Some(&item) = GENERAL_ITEMS.get(index) {
+            return item;
+        }
+        match index {
+            KEYMAP_PRESET_INDEX => SettingsItem::KeymapPreset,
+            index if index < KEYMAP_PRESET_INDEX => {
+                SettingsItem::Color(ThemeElement::try_from(index - GENERAL_ITEMS.len()).unwrap())
+            }
+            index => SettingsItem::Key(Action::try_from(index - KEYMAP_PRESET_INDEX - 1).unwrap()),
+        }
+    }
+
+    pub fn index(self) -> usize {
+        match self {
+            SettingsItem::Color(element) => GENERAL_ITEMS.len() + element as usize,
+            SettingsItem::KeymapPreset => KEYMAP_PRESET_INDEX,
+            SettingsItem::Key(action) => KEYMAP_PRESET_INDEX + 1 + action as usize,
+            item => GENERAL_ITEMS
+                .iter()
+                .position(|&general| general == item)
+                .unwrap(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingsItem::Rows => "Board rows",
+            SettingsItem::Cols => "Board columns",
+            SettingsItem::K => "Marks in a row to win",
+            SettingsItem::LogLevel => "Log level (on restart)",
+            SettingsItem::LogFormat => "Log format (on restart)",
+            SettingsItem::Animations => "Animations",
+            SettingsItem::ThemePreset => "Theme",
+            SettingsItem::Color(element) => element.label(),
+            SettingsItem::KeymapPreset => "Key preset",
+            SettingsItem::Key(action) => action.label(),
+        }
+    }
+
+    pub fn kind(self) -> SettingsKind {
+        match self {
+            SettingsItem::Rows | SettingsItem::Cols | SettingsItem::K => SettingsKind::Number,
+            SettingsItem::LogLevel
+            | SettingsItem::LogFormat
+            | SettingsItem::Animations
+            | SettingsItem::ThemePreset
+            | SettingsItem::KeymapPreset => SettingsKind::Choice,
+            SettingsItem::Color(_) => SettingsKind::Color,
+            SettingsItem::Key(_) => SettingsKind::Key,
+        }
+    }
+
+    pub fn value(self, config: &Config) -> String {
+        match self {
+            SettingsItem::Rows => config.board.rows.to_string(),
+            SettingsItem::Cols => config.board.cols.to_string(),
+            SettingsItem::K => config.board.k.to_string(),
+            SettingsItem::LogLevel => config.log_level.clone(),
+            SettingsItem::LogFormat => config.log_format.label().to_string(),
+            SettingsItem::Animations => if config.animations { "on" } else { "off" }.to_string(),
+            SettingsItem::ThemePreset => config.theme.preset.label().to_string(),
+            SettingsItem::Color(element) => config.theme.hex(element),
+            SettingsItem::KeymapPreset => config.keymap.preset.label().to_string(),
+            SettingsItem::Key(action) => config.keymap.keys(action).join(", "),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SettingsScreen {
+    pub config: Config,
+    pub selected: SettingsItem,
+    // text typed so far while editing a color
+    pub editing: Option<String>,
+    pub capturing_key: bool,
+    pub message: Option<String>,
+}
+
+impl SettingsScreen {
+    pub fn new(config: Config) -> Self {
+        Self {
+            config,
+            selected: SettingsItem::Rows,
+            editing: None,
+            capturing_key: false,
+            message: None,
+        }
+    }
+
+    pub fn select(&mut self, delta: isize) {
+        let index = self
+            .selected
+            .index()
+            .saturating_add_signed(delta)
+            .min(SETTINGS_ITEM_NUMS - 1);
+        self.selected = SettingsItem::from_index(index);
+    }
+
+    pub fn adjust(&mut self, delta: isize) {
+        let board = &mut self.config.board;
+        let step = |value: usize, max: usize| {
+            value
+                .saturating_add_signed(delta)
+                .clamp(MIN_BOARD_SIZE, max)
+        };
+        match self.selected {
+            SettingsItem::Rows => board.rows = step(board.rows, MAX_BOARD_SIZE),
+            SettingsItem::Cols => board.cols = step(board.cols, MAX_BOARD_SIZE),
+            SettingsItem::K => board.k = step(board.k, board.rows.max(board.cols)),
+            SettingsItem::LogLevel => {
+                let current = LOG_LEVELS
+                    .iter()
+                    .position(|&level| level == self.config.log_level)
+                    .unwrap_or(0);
+                let next = current
+                    .saturating_add_signed(delta)
+                    .min(LOG_LEVELS.len() - 1);
+                self.config.log_level = LOG_LEVELS[next].to_string();
+            }
+            SettingsItem::LogFormat => {
+                self.config.log_format = if delta > 0 {
+                    LogFormat::Json
+                } else {
+                    LogFormat::Text
+                }
+            }
+            // left turns them off, right on
+            SettingsItem::Animations => self.config.animations = delta > 0,
+            SettingsItem::ThemePreset => {
+                let theme = &mut self.config.theme;
+                let next = (theme.preset as usize)
+                    .saturating_add_signed(delta)
+                    .min(THEME_PRESET_NUMS - 1);
+                theme.preset = ThemePreset::try_from(next).unwrap();
+            }
+            SettingsItem::KeymapPreset => {
+                let keymap = &mut self.config.keymap;
+                let next = (keymap.preset as usize)
+                    .saturating_add_signed(delta)
+                    .min(KEYMAP_PRESET_NUMS - 1);
+                keymap.preset = KeymapPreset::try_from(next).unwrap();
+            }
+            _ => {}
+        }
+        // a smaller board may no longer fit the old k
+        self.config = self.config.clone().validated();
+    }
+
+    pub fn commit_color(&mut self, hex: String) {
+        if let Err(err) = HexColor::new_from_hex_str(&hex) {
+            self.message = Some(err.to_string());
+            return;
+        }
+        if let SettingsItem::Color(element) = self.selected {
+            self.config.theme.colors.insert(element, hex);
+        }
+        self.message = None;
+    }
+
+    pub fn commit_key(&mut self, key_name: String) {
+        let SettingsItem::Key(action) = self.selected else {
+            return;
+        };
+        let keymap = &mut self.config.keymap;
+        keymap.bind(action, key_name);
+        let conflicts: Vec<_> = keymap
+            .conflicts(action)
+            .iter()
+            .map(|other| other.label())
+            .collect();
+        self.message = (!conflicts.is_empty())
+            .then(|| format!("Key is also bound to: {}", conflicts.join(", ")));
+    }
+}
@@ -0,0 +1,19 @@
+use board_game::stats::Statistics;
+
+#[derive(Clone)]
+pub struct StatisticsScreen {
+    pub statistics: Statistics,
+    // reset asks for confirmation first
+    pub confirm_reset: bool,
+    pub error: Option<String>,
+}
+
+impl StatisticsScreen {
+    pub fn new(statistics: Statistics) -> Self {
+        Self {
+            statistics,
+            confirm_reset: false,
+            error: None,
+        }
+    }
+}
@@ -1,7 +1,16 @@
-use super::screen::{game_on_screen::GameOnScreen, main_screen::MainScreen};
+use super::screen::{
+    game_on_screen::GameOnScreen, game_over_screen::GameOverScreen, main_screen::MainScreen,
+    replay_screen::ReplayScreen, review_screen::ReviewScreen, settings_screen::SettingsScreen,
+    statistics_screen::StatisticsScreen,
+};
 
-#[derive(Clone, Copy)]
+#[derive(Clone)]
 pub enum CurrentScreen {
     Main(MainScreen),
     GameOn(GameOnScreen),
+    GameOver(GameOverScreen),
+    Review(ReviewScreen),
+    Statistics(StatisticsScreen),
+    Settings(SettingsScreen),
+    Replay(ReplayScreen),
 }